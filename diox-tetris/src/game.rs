@@ -1,9 +1,19 @@
 use rand::seq::SliceRandom;
 use rand::thread_rng;
+use std::collections::VecDeque;
 
-pub const WIDTH: usize = 10;
-pub const HEIGHT: usize = 20;
+pub const DEFAULT_WIDTH: usize = 10;
+pub const DEFAULT_HEIGHT: usize = 20;
 pub const BUFFER_HEIGHT: usize = 4;
+/// Supported board sizes, from 4-wide combo boards to 20x40 big boards
+pub const MIN_WIDTH: usize = 4;
+pub const MAX_WIDTH: usize = 20;
+pub const MIN_HEIGHT: usize = 4;
+pub const MAX_HEIGHT: usize = 40;
+const COMBO_BONUS: u32 = 50;
+/// Most pieces the next queue can show
+pub const MAX_PREVIEWS: usize = 6;
+pub const DEFAULT_PREVIEWS: usize = 5;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum GameState {
@@ -166,11 +176,13 @@ pub struct Piece {
 }
 
 impl Piece {
-  pub fn new(piece_type: PieceType) -> Self {
+  /// Piece at the spawn position of `board`
+  pub fn spawn(piece_type: PieceType, board: &Board) -> Self {
+    let (x, y) = board.spawn_position();
     Self {
       piece_type,
-      x: (WIDTH as i32) / 2 - 1,
-      y: HEIGHT as i32,
+      x,
+      y,
       rotation: Rotation::R0,
     }
   }
@@ -248,18 +260,46 @@ impl Piece {
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Board {
-  grid: [[Cell; WIDTH]; HEIGHT + BUFFER_HEIGHT],
+  width: usize,
+  height: usize,
+  // Bottom row first, height + BUFFER_HEIGHT rows
+  grid: Vec<Vec<Cell>>,
 }
 
 impl Board {
   pub fn new() -> Self {
+    Self::with_size(DEFAULT_WIDTH, DEFAULT_HEIGHT)
+  }
+
+  /// Empty board of the given visible size, clamped to the supported range
+  pub fn with_size(width: usize, height: usize) -> Self {
+    let width = width.clamp(MIN_WIDTH, MAX_WIDTH);
+    let height = height.clamp(MIN_HEIGHT, MAX_HEIGHT);
     Self {
-      grid: [[Cell::Empty; WIDTH]; HEIGHT + BUFFER_HEIGHT],
+      width,
+      height,
+      grid: vec![vec![Cell::Empty; width]; height + BUFFER_HEIGHT],
     }
   }
 
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  fn total_height(&self) -> usize {
+    self.height + BUFFER_HEIGHT
+  }
+
+  pub fn spawn_position(&self) -> (i32, i32) {
+    (self.width as i32 / 2 - 1, self.height as i32)
+  }
+
   pub fn get(&self, x: i32, y: i32) -> Option<Cell> {
-    if x < 0 || x >= WIDTH as i32 || y < 0 || y >= (HEIGHT + BUFFER_HEIGHT) as i32 {
+    if x < 0 || x >= self.width as i32 || y < 0 || y >= self.total_height() as i32 {
       None
     } else {
       Some(self.grid[y as usize][x as usize])
@@ -267,16 +307,16 @@ impl Board {
   }
 
   pub fn set(&mut self, x: i32, y: i32, cell: Cell) {
-    if x >= 0 && x < WIDTH as i32 && y >= 0 && y < (HEIGHT + BUFFER_HEIGHT) as i32 {
+    if x >= 0 && x < self.width as i32 && y >= 0 && y < self.total_height() as i32 {
       self.grid[y as usize][x as usize] = cell;
     }
   }
 
   pub fn is_valid_position(&self, x: i32, y: i32) -> bool {
-    if x < 0 || x >= WIDTH as i32 || y < 0 {
+    if x < 0 || x >= self.width as i32 || y < 0 {
       return false;
     }
-    if y >= (HEIGHT + BUFFER_HEIGHT) as i32 {
+    if y >= self.total_height() as i32 {
       return true;
     }
     self.grid[y as usize][x as usize].is_empty()
@@ -298,28 +338,27 @@ impl Board {
     let mut lines_cleared = 0;
     let mut write_row = 0;
 
-    for read_row in 0..(HEIGHT + BUFFER_HEIGHT) {
+    for read_row in 0..self.total_height() {
       let is_full = self.grid[read_row].iter().all(|&cell| !cell.is_empty());
       if is_full {
         lines_cleared += 1;
       } else {
         if write_row != read_row {
-          self.grid[write_row] = self.grid[read_row];
+          self.grid.swap(write_row, read_row);
         }
         write_row += 1;
       }
     }
 
-    while write_row < HEIGHT + BUFFER_HEIGHT {
-      self.grid[write_row] = [Cell::Empty; WIDTH];
-      write_row += 1;
+    for row in &mut self.grid[write_row..] {
+      row.fill(Cell::Empty);
     }
 
     lines_cleared
   }
 
   pub fn is_topped_out(&self) -> bool {
-    for row in HEIGHT..(HEIGHT + BUFFER_HEIGHT) {
+    for row in self.height..self.total_height() {
       if self.grid[row].iter().any(|&cell| !cell.is_empty()) {
         return true;
       }
@@ -332,32 +371,65 @@ impl Board {
 pub struct Game {
   pub board: Board,
   pub current_piece: Option<Piece>,
-  pub next_piece: PieceType,
   pub hold_piece: Option<PieceType>,
   pub can_hold: bool,
   pub score: u32,
   pub level: u32,
   pub lines_cleared: u32,
   pub game_over: bool,
+  pub combo: u32,
+  pub back_to_back: bool,
   bag: Vec<PieceType>,
+  // Upcoming pieces, always MAX_PREVIEWS long
+  queue: VecDeque<PieceType>,
+  preview_count: usize,
+}
+
+/// Settings fixed when a game is created
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GameConfig {
+  /// Visible next queue length (0 to MAX_PREVIEWS)
+  pub preview_count: usize,
+  /// Board size in cells, clamped to the range `Board` supports
+  pub width: usize,
+  pub height: usize,
+}
+
+impl Default for GameConfig {
+  fn default() -> Self {
+    Self {
+      preview_count: DEFAULT_PREVIEWS,
+      width: DEFAULT_WIDTH,
+      height: DEFAULT_HEIGHT,
+    }
+  }
 }
 
 impl Game {
   pub fn new() -> Self {
+    Self::with_config(GameConfig::default())
+  }
+
+  pub fn with_config(config: GameConfig) -> Self {
     let mut game = Self {
-      board: Board::new(),
+      board: Board::with_size(config.width, config.height),
       current_piece: None,
-      next_piece: PieceType::T,
       hold_piece: None,
       can_hold: true,
       score: 0,
       level: 1,
       lines_cleared: 0,
       game_over: false,
+      combo: 0,
+      back_to_back: false,
       bag: Vec::new(),
+      queue: VecDeque::with_capacity(MAX_PREVIEWS),
+      preview_count: config.preview_count.min(MAX_PREVIEWS),
     };
-    game.refill_bag();
-    game.next_piece = game.draw_from_bag();
+    for _ in 0..MAX_PREVIEWS {
+      let piece = game.draw_from_bag();
+      game.queue.push_back(piece);
+    }
     game.spawn_piece();
     game
   }
@@ -375,10 +447,20 @@ impl Game {
     self.bag.pop().unwrap()
   }
 
+  /// Up to `n` upcoming pieces, limited to the visible preview count
+  pub fn peek_queue(&self, n: usize) -> Vec<PieceType> {
+    self.queue.iter().take(n.min(self.preview_count)).copied().collect()
+  }
+
+  pub fn preview_count(&self) -> usize {
+    self.preview_count
+  }
+
   pub fn spawn_piece(&mut self) -> bool {
-    let piece_type = self.next_piece;
-    self.next_piece = self.draw_from_bag();
-    let piece = Piece::new(piece_type);
+    let piece_type = self.queue.pop_front().unwrap();
+    let refill = self.draw_from_bag();
+    self.queue.push_back(refill);
+    let piece = Piece::spawn(piece_type, &self.board);
 
     let blocks = piece.get_blocks();
     if self.board.check_collision(&blocks) {
@@ -498,7 +580,11 @@ impl Game {
     if lines > 0 {
       self.lines_cleared += lines;
       self.score += self.calculate_line_score(lines);
+      self.combo += 1;
+      self.back_to_back = lines == 4;
       self.update_level();
+    } else {
+      self.combo = 0;
     }
 
     if self.board.is_topped_out() {
@@ -521,7 +607,11 @@ impl Game {
       4 => 800,
       _ => 0,
     };
-    base * self.level
+    let mut points = base * self.level;
+    if lines == 4 && self.back_to_back {
+      points = points * 3 / 2;
+    }
+    points + COMBO_BONUS * self.combo * self.level
   }
 
   fn update_level(&mut self) {
@@ -541,7 +631,7 @@ impl Game {
 
     if let Some(held) = self.hold_piece {
       self.hold_piece = Some(current_type);
-      self.current_piece = Some(Piece::new(held));
+      self.current_piece = Some(Piece::spawn(held, &self.board));
     } else {
       self.hold_piece = Some(current_type);
       self.spawn_piece();
@@ -593,8 +683,8 @@ impl Game {
 
   pub fn get_board_cells(&self) -> Vec<(i32, i32, u8)> {
     let mut cells = Vec::new();
-    for y in 0..HEIGHT {
-      for x in 0..WIDTH {
+    for y in 0..self.board.height() {
+      for x in 0..self.board.width() {
         let cell = self.board.get(x as i32, y as i32).unwrap_or(Cell::Empty);
         if !cell.is_empty() {
           cells.push((x as i32, y as i32, cell as u8));
@@ -3,7 +3,7 @@
 mod game;
 
 use dioxus::prelude::*;
-use game::{Game, GameState, MoveResult};
+use game::{Game, GameConfig, GameState, MoveResult};
 use gloo_net::http::Request;
 use gloo_timers::future::TimeoutFuture;
 use serde::{Deserialize, Serialize};
@@ -11,10 +11,13 @@ use std::rc::Rc;
 use wasm_bindgen_futures::spawn_local;
 
 const CELL_SIZE: u32 = 30;
-const BOARD_WIDTH: usize = 10;
-const BOARD_HEIGHT: usize = 20;
+/// Tall boards are scaled down (via the SVG viewBox) to fit this height
+const MAX_BOARD_PIXELS: u32 = 600;
 // Shared API endpoint for all Tetris games
 const API_BASE: &str = "https://tetris-api.mgavriliu.deno.net/api";
+/// This port doesn't record replays, which the main leaderboard requires, so its
+/// scores go on the API's separate leaderboard of unverified scores
+const SCORES_PATH: &str = "scores/unverified";
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Score {
@@ -22,19 +25,40 @@ pub struct Score {
     pub score: u32,
     pub level: u32,
     pub lines: u32,
+    /// Scoring ruleset; this port only plays guideline scoring
+    #[serde(default)]
+    pub ruleset: String,
     #[serde(default)]
     pub timestamp: u64,
 }
 
+const RULESET: &str = "guideline";
+
+/// Board size from the `?width=<4-20>&height=<4-40>` URL parameters, defaulting to 10x20
+fn game_config() -> GameConfig {
+    let mut config = GameConfig::default();
+    let search = web_sys::window()
+        .and_then(|w| w.location().search().ok())
+        .unwrap_or_default();
+    for pair in search.trim_start_matches('?').split('&') {
+        match pair.split_once('=') {
+            Some(("width", value)) => config.width = value.parse().unwrap_or(config.width),
+            Some(("height", value)) => config.height = value.parse().unwrap_or(config.height),
+            _ => {}
+        }
+    }
+    config
+}
+
 async fn fetch_high_scores() -> Vec<Score> {
-    match Request::get(&format!("{}/scores", API_BASE)).send().await {
+    match Request::get(&format!("{}/{}", API_BASE, SCORES_PATH)).send().await {
         Ok(resp) => resp.json().await.unwrap_or_default(),
         Err(_) => vec![],
     }
 }
 
 async fn submit_score(score: &Score) -> bool {
-    match Request::post(&format!("{}/scores", API_BASE))
+    match Request::post(&format!("{}/{}", API_BASE, SCORES_PATH))
         .header("Content-Type", "application/json")
         .body(serde_json::to_string(score).unwrap())
         .unwrap()
@@ -52,7 +76,7 @@ fn main() {
 
 #[component]
 fn App() -> Element {
-    let mut game = use_signal(|| Game::new());
+    let mut game = use_signal(|| Game::with_config(game_config()));
     let mut state = use_signal(|| GameState::Idle);
     let mut high_scores = use_signal(Vec::<Score>::new);
     let player_name = use_signal(String::new);
@@ -133,7 +157,7 @@ fn App() -> Element {
         // Start game
         if matches!(key, Key::Enter) || matches!(&key, Key::Character(c) if c == " ") {
             if current_state == GameState::Idle || current_state == GameState::GameOver {
-                game.set(Game::new());
+                game.set(Game::with_config(game_config()));
                 state.set(GameState::Playing);
                 score_submitted.set(false);
                 return;
@@ -161,7 +185,7 @@ fn App() -> Element {
 
         // Restart
         if matches!(&key, Key::Character(c) if c == "r" || c == "R") {
-            game.set(Game::new());
+            game.set(Game::with_config(game_config()));
             state.set(GameState::Playing);
             score_submitted.set(false);
             return;
@@ -191,6 +215,12 @@ fn App() -> Element {
     let scores = high_scores.read();
     let showing_input = *show_name_input.read();
     let submitted = *score_submitted.read();
+    let board_width = game_data.board.width();
+    let board_height = game_data.board.height();
+    let board_px_width = board_width as u32 * CELL_SIZE;
+    let board_px_height = board_height as u32 * CELL_SIZE;
+    let display_height = board_px_height.min(MAX_BOARD_PIXELS);
+    let display_width = board_px_width * display_height / board_px_height;
 
     rsx! {
         div {
@@ -207,7 +237,7 @@ fn App() -> Element {
                     div { class: "panel-section",
                         div { class: "panel-label", "HOLD [C]" }
                         div { class: "preview-container",
-                            PreviewPiece { piece_type: game_data.hold_piece }
+                            PreviewPiece { pieces: game_data.hold_piece.into_iter().collect(), slots: 1 }
                         }
                     }
                     div { class: "panel-section",
@@ -231,13 +261,14 @@ fn App() -> Element {
                 // Game board
                 div { class: "board-container",
                     svg {
-                        width: "{BOARD_WIDTH as u32 * CELL_SIZE}",
-                        height: "{BOARD_HEIGHT as u32 * CELL_SIZE}",
+                        width: "{display_width}",
+                        height: "{display_height}",
+                        view_box: "0 0 {board_px_width} {board_px_height}",
                         class: "tetris-board",
 
                         // Grid background
-                        for y in 0..BOARD_HEIGHT {
-                            for x in 0..BOARD_WIDTH {
+                        for y in 0..board_height {
+                            for x in 0..board_width {
                                 rect {
                                     x: "{x as u32 * CELL_SIZE}",
                                     y: "{y as u32 * CELL_SIZE}",
@@ -261,13 +292,18 @@ fn App() -> Element {
 
                         // Overlays
                         if current_state == GameState::Idle {
-                            StartOverlay {}
+                            StartOverlay { width: board_px_width, height: board_px_height }
                         }
                         if current_state == GameState::Paused {
-                            PauseOverlay {}
+                            PauseOverlay { width: board_px_width, height: board_px_height }
                         }
                         if current_state == GameState::GameOver && !showing_input {
-                            GameOverOverlay { score: game_data.score, submitted: submitted }
+                            GameOverOverlay {
+                                score: game_data.score,
+                                submitted: submitted,
+                                width: board_px_width,
+                                height: board_px_height,
+                            }
                         }
                     }
 
@@ -290,7 +326,10 @@ fn App() -> Element {
                     div { class: "panel-section",
                         div { class: "panel-label", "NEXT" }
                         div { class: "preview-container",
-                            PreviewPiece { piece_type: Some(game_data.next_piece) }
+                            PreviewPiece {
+                                pieces: game_data.peek_queue(game_data.preview_count()),
+                                slots: game_data.preview_count(),
+                            }
                         }
                     }
                     div { class: "panel-section",
@@ -305,6 +344,13 @@ fn App() -> Element {
                         div { class: "panel-label", "LINES" }
                         div { class: "stat-value", "{game_data.lines_cleared}" }
                     }
+                    div { class: "panel-section",
+                        div { class: "panel-label", "COMBO" }
+                        div { class: "stat-value", "{game_data.combo.saturating_sub(1)}" }
+                        div { class: "clear-label",
+                            if game_data.back_to_back { "BACK-TO-BACK" }
+                        }
+                    }
                 }
             }
         }
@@ -313,13 +359,14 @@ fn App() -> Element {
 
 #[component]
 fn BoardCells(game: Rc<Game>) -> Element {
+    let board_height = game.board.height();
     let cells = game.get_board_cells();
 
     rsx! {
         for (x, y, cell) in cells {
             rect {
                 x: "{x as u32 * CELL_SIZE + 1}",
-                y: "{(BOARD_HEIGHT - 1 - y as usize) as u32 * CELL_SIZE + 1}",
+                y: "{(board_height - 1 - y as usize) as u32 * CELL_SIZE + 1}",
                 width: "{CELL_SIZE - 2}",
                 height: "{CELL_SIZE - 2}",
                 fill: "{cell_color(cell)}",
@@ -331,6 +378,7 @@ fn BoardCells(game: Rc<Game>) -> Element {
 
 #[component]
 fn CurrentPiece(game: Rc<Game>) -> Element {
+    let board_height = game.board.height();
     let Some(piece) = &game.current_piece else {
         return rsx! {};
     };
@@ -340,10 +388,10 @@ fn CurrentPiece(game: Rc<Game>) -> Element {
 
     rsx! {
         for (x, y) in blocks {
-            if y >= 0 && y < BOARD_HEIGHT as i32 {
+            if y >= 0 && y < board_height as i32 {
                 rect {
                     x: "{x as u32 * CELL_SIZE + 1}",
-                    y: "{(BOARD_HEIGHT - 1 - y as usize) as u32 * CELL_SIZE + 1}",
+                    y: "{(board_height - 1 - y as usize) as u32 * CELL_SIZE + 1}",
                     width: "{CELL_SIZE - 2}",
                     height: "{CELL_SIZE - 2}",
                     fill: "{color}",
@@ -356,6 +404,7 @@ fn CurrentPiece(game: Rc<Game>) -> Element {
 
 #[component]
 fn GhostPiece(game: Rc<Game>) -> Element {
+    let board_height = game.board.height();
     let Some(ghost_y) = game.get_ghost_y() else {
         return rsx! {};
     };
@@ -372,11 +421,11 @@ fn GhostPiece(game: Rc<Game>) -> Element {
         for (x, y) in blocks {
             {
                 let gy = y - dy;
-                if gy >= 0 && gy < BOARD_HEIGHT as i32 {
+                if gy >= 0 && gy < board_height as i32 {
                     rsx! {
                         rect {
                             x: "{x as u32 * CELL_SIZE + 1}",
-                            y: "{(BOARD_HEIGHT - 1 - gy as usize) as u32 * CELL_SIZE + 1}",
+                            y: "{(board_height - 1 - gy as usize) as u32 * CELL_SIZE + 1}",
                             width: "{CELL_SIZE - 2}",
                             height: "{CELL_SIZE - 2}",
                             fill: "{color}",
@@ -392,32 +441,30 @@ fn GhostPiece(game: Rc<Game>) -> Element {
     }
 }
 
+/// Vertical stack of piece previews, `slots` tall, nearest piece on top
 #[component]
-fn PreviewPiece(piece_type: Option<game::PieceType>) -> Element {
-    let Some(pt) = piece_type else {
-        return rsx! {
-            svg { width: "96", height: "48", class: "preview-svg" }
-        };
-    };
-
-    let blocks = pt.preview_blocks();
-    let color = piece_color(pt);
+fn PreviewPiece(pieces: Vec<game::PieceType>, slots: usize) -> Element {
     let cell_size = 24;
+    // 2 rows per piece plus half a row between slots
+    let slot_height = cell_size * 5 / 2;
+    let height = (slots as u32 * slot_height).saturating_sub(cell_size / 2);
 
     rsx! {
         svg {
             width: "96",
-            height: "48",
+            height: "{height}",
             class: "preview-svg",
 
-            for (x, y) in blocks {
-                rect {
-                    x: "{(x + 1) as u32 * cell_size + 1}",
-                    y: "{(1 - y) as u32 * cell_size + 1}",
-                    width: "{cell_size - 2}",
-                    height: "{cell_size - 2}",
-                    fill: "{color}",
-                    rx: "2",
+            for (i, pt) in pieces.into_iter().enumerate() {
+                for (x, y) in pt.preview_blocks() {
+                    rect {
+                        x: "{(x + 1) as u32 * cell_size + 1}",
+                        y: "{i as u32 * slot_height + (1 - y) as u32 * cell_size + 1}",
+                        width: "{cell_size - 2}",
+                        height: "{cell_size - 2}",
+                        fill: "{piece_color(pt)}",
+                        rx: "2",
+                    }
                 }
             }
         }
@@ -425,16 +472,16 @@ fn PreviewPiece(piece_type: Option<game::PieceType>) -> Element {
 }
 
 #[component]
-fn StartOverlay() -> Element {
+fn StartOverlay(width: u32, height: u32) -> Element {
     rsx! {
         g {
             rect {
-                width: "{BOARD_WIDTH as u32 * CELL_SIZE}",
-                height: "{BOARD_HEIGHT as u32 * CELL_SIZE}",
+                width: "{width}",
+                height: "{height}",
                 fill: "rgba(0,0,0,0.8)",
             }
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
+                x: "{width / 2}",
                 y: "80",
                 text_anchor: "middle",
                 fill: "#00f5ff",
@@ -444,7 +491,7 @@ fn StartOverlay() -> Element {
                 "TETRIS"
             }
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
+                x: "{width / 2}",
                 y: "110",
                 text_anchor: "middle",
                 fill: "#888",
@@ -454,7 +501,7 @@ fn StartOverlay() -> Element {
             }
             // Controls header
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
+                x: "{width / 2}",
                 y: "160",
                 text_anchor: "middle",
                 fill: "#9d4edd",
@@ -465,7 +512,7 @@ fn StartOverlay() -> Element {
             }
             // Move
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
+                x: "{width / 2}",
                 y: "190",
                 text_anchor: "middle",
                 fill: "#aaa",
@@ -475,7 +522,7 @@ fn StartOverlay() -> Element {
             }
             // Soft drop
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
+                x: "{width / 2}",
                 y: "210",
                 text_anchor: "middle",
                 fill: "#aaa",
@@ -485,7 +532,7 @@ fn StartOverlay() -> Element {
             }
             // Hard drop
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
+                x: "{width / 2}",
                 y: "230",
                 text_anchor: "middle",
                 fill: "#aaa",
@@ -495,7 +542,7 @@ fn StartOverlay() -> Element {
             }
             // Rotate CW
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
+                x: "{width / 2}",
                 y: "250",
                 text_anchor: "middle",
                 fill: "#aaa",
@@ -505,7 +552,7 @@ fn StartOverlay() -> Element {
             }
             // Rotate CCW
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
+                x: "{width / 2}",
                 y: "270",
                 text_anchor: "middle",
                 fill: "#aaa",
@@ -515,7 +562,7 @@ fn StartOverlay() -> Element {
             }
             // Hold
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
+                x: "{width / 2}",
                 y: "290",
                 text_anchor: "middle",
                 fill: "#aaa",
@@ -525,7 +572,7 @@ fn StartOverlay() -> Element {
             }
             // Pause
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
+                x: "{width / 2}",
                 y: "310",
                 text_anchor: "middle",
                 fill: "#aaa",
@@ -538,17 +585,17 @@ fn StartOverlay() -> Element {
 }
 
 #[component]
-fn PauseOverlay() -> Element {
+fn PauseOverlay(width: u32, height: u32) -> Element {
     rsx! {
         g {
             rect {
-                width: "{BOARD_WIDTH as u32 * CELL_SIZE}",
-                height: "{BOARD_HEIGHT as u32 * CELL_SIZE}",
+                width: "{width}",
+                height: "{height}",
                 fill: "rgba(0,0,0,0.7)",
             }
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
-                y: "{BOARD_HEIGHT as u32 * CELL_SIZE / 2}",
+                x: "{width / 2}",
+                y: "{height / 2}",
                 text_anchor: "middle",
                 fill: "#fff",
                 font_size: "24",
@@ -556,8 +603,8 @@ fn PauseOverlay() -> Element {
                 "PAUSED"
             }
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
-                y: "{BOARD_HEIGHT as u32 * CELL_SIZE / 2 + 30}",
+                x: "{width / 2}",
+                y: "{height / 2 + 30}",
                 text_anchor: "middle",
                 fill: "#888",
                 font_size: "14",
@@ -586,6 +633,7 @@ fn NameInputOverlay(
                 score,
                 level,
                 lines,
+                ruleset: RULESET.to_string(),
                 timestamp: 0,
             };
             spawn_local(async move {
@@ -621,6 +669,7 @@ fn NameInputOverlay(
                                     score,
                                     level,
                                     lines,
+                                    ruleset: RULESET.to_string(),
                                     timestamp: 0,
                                 };
                                 spawn(async move {
@@ -656,17 +705,17 @@ fn NameInputOverlay(
 }
 
 #[component]
-fn GameOverOverlay(score: u32, submitted: bool) -> Element {
+fn GameOverOverlay(score: u32, submitted: bool, width: u32, height: u32) -> Element {
     rsx! {
         g {
             rect {
-                width: "{BOARD_WIDTH as u32 * CELL_SIZE}",
-                height: "{BOARD_HEIGHT as u32 * CELL_SIZE}",
+                width: "{width}",
+                height: "{height}",
                 fill: "rgba(0,0,0,0.7)",
             }
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
-                y: "{BOARD_HEIGHT as u32 * CELL_SIZE / 2 - 20}",
+                x: "{width / 2}",
+                y: "{height / 2 - 20}",
                 text_anchor: "middle",
                 fill: "#ff6b6b",
                 font_size: "24",
@@ -674,8 +723,8 @@ fn GameOverOverlay(score: u32, submitted: bool) -> Element {
                 "GAME OVER"
             }
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
-                y: "{BOARD_HEIGHT as u32 * CELL_SIZE / 2 + 10}",
+                x: "{width / 2}",
+                y: "{height / 2 + 10}",
                 text_anchor: "middle",
                 fill: "#fff",
                 font_size: "16",
@@ -684,8 +733,8 @@ fn GameOverOverlay(score: u32, submitted: bool) -> Element {
             }
             if submitted {
                 text {
-                    x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
-                    y: "{BOARD_HEIGHT as u32 * CELL_SIZE / 2 + 35}",
+                    x: "{width / 2}",
+                    y: "{height / 2 + 35}",
                     text_anchor: "middle",
                     fill: "#00f5ff",
                     font_size: "12",
@@ -694,8 +743,8 @@ fn GameOverOverlay(score: u32, submitted: bool) -> Element {
                 }
             }
             text {
-                x: "{BOARD_WIDTH as u32 * CELL_SIZE / 2}",
-                y: "{BOARD_HEIGHT as u32 * CELL_SIZE / 2 + 60}",
+                x: "{width / 2}",
+                y: "{height / 2 + 60}",
                 text_anchor: "middle",
                 fill: "#888",
                 font_size: "14",
@@ -716,6 +765,7 @@ fn cell_color(cell: u8) -> &'static str {
         5 => "#ff6b6b",
         6 => "#4169e1",
         7 => "#ff8c00",
+        8 => "#6c6c7e",
         _ => "#1a1a2e",
     }
 }
@@ -1,7 +1,16 @@
-use crate::game::{Game, MoveResult};
+use crate::events::GameEvent;
+use crate::game::{Game, GameConfig, MoveResult};
+use crate::gravity::{gravity_units, FRAMES_PER_SECOND, GRAVITY_UNIT, MAX_GRAVITY};
 use crate::input::{Action, InputState};
+use crate::mode::{master_delays, master_grade, GameMode, MASTER_MAX_LEVEL, MASTER_SECTION_LEVELS};
+use crate::piece::Turn;
 use crate::render::RenderState;
+use crate::replay::{InputEvent, Replay};
+use crate::scoring::ScoringKind;
+use crate::stats::Statistics;
+use crate::{KEY_REDO, KEY_RESTART, KEY_START, KEY_UNDO};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
 /// Game states
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -10,15 +19,184 @@ pub enum GameState {
     Playing,
     Paused,
     GameOver,
+    /// The mode's goal was reached (e.g. the Sprint line goal)
+    Finished,
+}
+
+/// Default lock delay in milliseconds
+pub const LOCK_DELAY_MS: f64 = 500.0;
+/// Default number of lock delay resets allowed per piece under step reset
+pub const LOCK_RESET_LIMIT: u32 = 15;
+
+/// How moving or rotating a grounded piece affects its lock timer
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum LockReset {
+    /// Every successful move or rotation restarts the timer
+    Infinite,
+    /// Moves and rotations restart the timer at most this many times per piece
+    Step(u32),
+    /// Classic rules: only falling to a new lowest row restarts the timer
+    NoReset,
+}
+
+/// Lock delay settings
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LockDelay {
+    pub duration_ms: f64,
+    pub reset: LockReset,
+}
+
+impl Default for LockDelay {
+    fn default() -> Self {
+        Self {
+            duration_ms: LOCK_DELAY_MS,
+            reset: LockReset::Step(LOCK_RESET_LIMIT),
+        }
+    }
+}
+
+/// Most placements that can be undone
+pub const MAX_UNDO: usize = 100;
+
+/// Length of one frame at 60Hz, the unit classic delays are specified in
+pub const FRAME_MS: f64 = 1000.0 / FRAMES_PER_SECOND;
+
+/// Whole frames closest to `ms`
+fn ms_to_frames(ms: f64) -> u32 {
+    (ms / FRAME_MS).round() as u32
+}
+
+/// Delays between a piece locking and the next one spawning
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct EntryDelay {
+    /// ARE: wait after a lock before the next piece appears
+    pub are_ms: f64,
+    /// Wait while cleared rows are still shown, before ARE starts
+    pub line_clear_ms: f64,
+}
+
+impl Default for EntryDelay {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl EntryDelay {
+    /// Next piece appears at once (modern guideline games)
+    pub fn none() -> Self {
+        Self {
+            are_ms: 0.0,
+            line_clear_ms: 0.0,
+        }
+    }
+
+    /// Short guideline console delays: 6 frames ARE, 20 frame line clear
+    pub fn guideline() -> Self {
+        Self {
+            are_ms: 6.0 * FRAME_MS,
+            line_clear_ms: 20.0 * FRAME_MS,
+        }
+    }
+
+    /// NES: 10 frames ARE, 18 frame line clear animation
+    pub fn nes() -> Self {
+        Self {
+            are_ms: 10.0 * FRAME_MS,
+            line_clear_ms: 18.0 * FRAME_MS,
+        }
+    }
+
+    /// TGM: 30 frames ARE, 41 frame line clear
+    pub fn tgm() -> Self {
+        Self {
+            are_ms: 30.0 * FRAME_MS,
+            line_clear_ms: 41.0 * FRAME_MS,
+        }
+    }
+
+    /// Preset by number: 0=none, 1=guideline, 2=NES, 3=TGM
+    pub fn from_u8(kind: u8) -> Option<Self> {
+        match kind {
+            0 => Some(Self::none()),
+            1 => Some(Self::guideline()),
+            2 => Some(Self::nes()),
+            3 => Some(Self::tgm()),
+            _ => None,
+        }
+    }
+
+    fn is_instant(&self) -> bool {
+        self.are_ms <= 0.0 && self.line_clear_ms <= 0.0
+    }
+}
+
+/// Where the controller is between locking one piece and spawning the next
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Phase {
+    /// A piece is in play
+    Falling,
+    /// Cleared rows are shown for the remaining frames
+    LineClear(u32),
+    /// ARE: the next piece spawns after the remaining frames
+    Entry(u32),
+}
+
+/// Replay being played back, with the player's own settings to restore afterwards
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Playback {
+    inputs: Vec<InputEvent>,
+    next: usize,
+    config: GameConfig,
+    lock_delay: LockDelay,
+    entry_delay: EntryDelay,
 }
 
 /// Main game controller - combines state machine, input, and game logic
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GameController {
     pub state: GameState,
     pub game: Option<Game>,
     pub input: InputState,
-    drop_accumulator: f64,
+    pub lock_delay: LockDelay,
+    /// Delays between pieces; takes effect on the next game
+    pub entry_delay: EntryDelay,
+    /// Settings for every new game; a `None` seed picks a fresh one each game
+    pub config: GameConfig,
+    // Time toward the next frame, in 1/60 ms
+    frame_time: f64,
+    // Gravity progress of the current piece, in GRAVITY_UNIT per row
+    gravity_counter: u64,
+    phase: Phase,
+    // Frames played in the current game, not counting pauses
+    frames: u64,
+    // Frames since the last rising garbage row
+    garbage_timer: u32,
+    // Play time of each finished Master section (ms)
+    section_times: Vec<f64>,
+    // Frames the current piece has spent on the ground
+    lock_timer: u32,
+    // Lock delay resets used by the current piece
+    lock_resets: u32,
+    // Lowest row the current piece has reached
+    lowest_y: i32,
+    // Undrained events of games replaced by a restart
+    events: Vec<GameEvent>,
+    // Key events of the current game so far
+    recording: Option<Replay>,
+    playback: Option<Playback>,
+    // Game as the current piece spawned, until it locks
+    #[serde(default)]
+    piece_start: Option<Game>,
+    // Games as each of the last placed pieces spawned, most recent last. Left
+    // out of snapshots, which would otherwise hold up to MAX_UNDO games.
+    #[serde(skip)]
+    undo_history: VecDeque<Game>,
+    // Games undone, most recently undone last; also left out of snapshots
+    #[serde(skip)]
+    redo_history: Vec<Game>,
+    // Set by the first undo; such a game doesn't count for the leaderboard
+    #[serde(default)]
+    practice: bool,
 }
 
 impl Default for GameController {
@@ -33,16 +211,79 @@ impl GameController {
             state: GameState::Idle,
             game: None,
             input: InputState::new(),
-            drop_accumulator: 0.0,
+            lock_delay: LockDelay::default(),
+            entry_delay: EntryDelay::default(),
+            config: GameConfig::default(),
+            frame_time: 0.0,
+            gravity_counter: 0,
+            phase: Phase::Falling,
+            frames: 0,
+            garbage_timer: 0,
+            section_times: Vec::new(),
+            lock_timer: 0,
+            lock_resets: 0,
+            lowest_y: i32::MAX,
+            events: Vec::new(),
+            recording: None,
+            playback: None,
+            piece_start: None,
+            undo_history: VecDeque::new(),
+            redo_history: Vec::new(),
+            practice: false,
         }
     }
 
     /// Start a new game
     pub fn start(&mut self) {
-        self.game = Some(Game::new());
+        self.stop_replay();
+        self.begin();
+    }
+
+    fn begin(&mut self) {
+        if let Some(game) = &mut self.game {
+            self.events.extend(game.drain_events());
+        }
+        let game = Game::with_config(self.config.clone());
+        self.recording = Some(Replay {
+            config: GameConfig {
+                seed: Some(game.seed),
+                ..self.config.clone()
+            },
+            lock_delay: self.lock_delay,
+            entry_delay: self.entry_delay,
+            inputs: Vec::new(),
+        });
+        self.game = Some(game);
+        let spawn_on_lock = self.current_entry_delay().is_instant();
+        if let Some(game) = &mut self.game {
+            game.spawn_on_lock = spawn_on_lock;
+        }
         self.state = GameState::Playing;
         self.input.reset();
-        self.drop_accumulator = 0.0;
+        self.frame_time = 0.0;
+        self.phase = Phase::Falling;
+        self.frames = 0;
+        self.garbage_timer = 0;
+        self.section_times.clear();
+        self.reset_piece_timers();
+        self.apply_instant_gravity();
+        self.piece_start = None;
+        self.undo_history.clear();
+        self.redo_history.clear();
+        self.practice = false;
+        self.track_history();
+    }
+
+    /// Start a new game with a fixed piece sequence; restarts reuse the seed
+    pub fn start_with_seed(&mut self, seed: u64) {
+        self.config.seed = Some(seed);
+        self.start();
+    }
+
+    /// Start a new game at `level`; restarts keep the start level
+    pub fn start_at_level(&mut self, level: u32) {
+        self.config.start_level = level.max(1);
+        self.start();
     }
 
     /// Pause the game
@@ -65,19 +306,131 @@ impl GameController {
         self.start();
     }
 
+    /// Play `replay` back: a new game under its settings, fed its key events on
+    /// their frames as `update` runs. Only pause, start and restart keys work
+    /// meanwhile; starting another game brings back the player's settings.
+    pub fn play_replay(&mut self, replay: &Replay) {
+        self.stop_replay();
+        let playback = Playback {
+            inputs: replay.inputs.clone(),
+            next: 0,
+            config: std::mem::replace(&mut self.config, replay.config.clone()),
+            lock_delay: std::mem::replace(&mut self.lock_delay, replay.lock_delay),
+            entry_delay: std::mem::replace(&mut self.entry_delay, replay.entry_delay),
+        };
+        self.begin();
+        self.playback = Some(playback);
+        self.feed_replay();
+    }
+
+    /// Stop feeding replay inputs and restore the player's settings
+    pub fn stop_replay(&mut self) {
+        if let Some(playback) = self.playback.take() {
+            self.config = playback.config;
+            self.lock_delay = playback.lock_delay;
+            self.entry_delay = playback.entry_delay;
+        }
+    }
+
+    /// Whether the current game is a replay being played back
+    pub fn is_replaying(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// Recording of the current (or last) game
+    pub fn replay(&self) -> Option<&Replay> {
+        self.recording.as_ref()
+    }
+
     /// Handle key down event
-    /// key: 0=left, 1=right, 2=down, 3=space, 4=up/x, 5=z, 6=c/shift, 7=p/esc, 8=enter/space(start), 9=r
+    /// key: 0=left, 1=right, 2=down, 3=space, 4=up/x, 5=z, 6=c/shift, 7=p/esc, 8=enter/space(start), 9=r,
+    /// 10=a, 11=undo, 12=redo
     pub fn key_down(&mut self, key: u8) {
-        if let Some(action) = self.input.key_down(key) {
-            self.handle_action(action);
+        // A replay plays its own inputs; the player can only pause or leave it
+        if self.playback.is_some() {
+            match (key, self.state) {
+                // Without the input reset of `pause`, which would let go of keys the replay holds
+                (7, GameState::Playing) => self.state = GameState::Paused,
+                (7, GameState::Paused) => self.state = GameState::Playing,
+                (8 | 9, _) => self.press(key),
+                _ => {}
+            }
+            return;
         }
+        self.press(key);
     }
 
     /// Handle key up event
     pub fn key_up(&mut self, key: u8) {
+        if self.playback.is_none() {
+            self.release(key);
+        }
+    }
+
+    fn press(&mut self, key: u8) {
+        self.record(key, true);
+        if let Some(action) = self.input.key_down(key) {
+            // Key presses only: DAS repeats don't count toward keys per piece
+            if self.state == GameState::Playing && action.acts_on_piece() {
+                if let Some(game) = &mut self.game {
+                    game.stats.record_key();
+                }
+            }
+            self.handle_action(action);
+        }
+    }
+
+    fn release(&mut self, key: u8) {
+        self.record(key, false);
         self.input.key_up(key);
     }
 
+    /// Add a key event to the recording of the game in progress. Start and
+    /// restart are left out: they begin another game rather than play this one.
+    /// Undo and redo are recorded by `undo` and `redo` when they take effect,
+    /// which can be after the game is over.
+    fn record(&mut self, key: u8, pressed: bool) {
+        if !matches!(self.state, GameState::Playing | GameState::Paused)
+            || matches!(key, KEY_START | KEY_RESTART | KEY_UNDO | KEY_REDO)
+        {
+            return;
+        }
+        self.record_input(key, pressed);
+    }
+
+    fn record_input(&mut self, key: u8, pressed: bool) {
+        if let Some(recording) = &mut self.recording {
+            recording.inputs.push(InputEvent {
+                frame: self.frames,
+                key,
+                pressed,
+            });
+        }
+    }
+
+    /// Apply the replay's key events up to the current frame, as they came in
+    /// after that frame in the recorded game
+    fn feed_replay(&mut self) {
+        while let Some(input) = self
+            .playback
+            .as_ref()
+            .and_then(|playback| playback.inputs.get(playback.next))
+            .copied()
+        {
+            if input.frame > self.frames {
+                break;
+            }
+            if let Some(playback) = &mut self.playback {
+                playback.next += 1;
+            }
+            if input.pressed {
+                self.press(input.key);
+            } else {
+                self.release(input.key);
+            }
+        }
+    }
+
     /// Handle an action
     fn handle_action(&mut self, action: Action) {
         // Handle state transitions first (without borrowing game)
@@ -90,10 +443,12 @@ impl GameController {
                 self.pause();
                 return;
             }
-            (GameState::Playing, Action::Restart) |
-            (GameState::Paused, Action::Restart) |
-            (GameState::GameOver, Action::Restart) |
-            (GameState::GameOver, Action::Start) => {
+            (GameState::Playing, Action::Restart)
+            | (GameState::Paused, Action::Restart)
+            | (GameState::GameOver, Action::Restart)
+            | (GameState::GameOver, Action::Start)
+            | (GameState::Finished, Action::Restart)
+            | (GameState::Finished, Action::Start) => {
                 self.restart();
                 return;
             }
@@ -101,74 +456,430 @@ impl GameController {
                 self.resume();
                 return;
             }
+            (GameState::Playing, Action::Undo) | (GameState::GameOver, Action::Undo) => {
+                self.undo();
+                return;
+            }
+            (GameState::Playing, Action::Redo) => {
+                self.redo();
+                return;
+            }
             _ => {}
         }
 
         // Handle game actions
-        if self.state == GameState::Playing {
-            if let Some(game) = &mut self.game {
-                match action {
-                    Action::MoveLeft => { game.move_piece(-1, 0); }
-                    Action::MoveRight => { game.move_piece(1, 0); }
-                    Action::SoftDrop => { game.soft_drop(); }
-                    Action::HardDrop => { game.hard_drop(); }
-                    Action::RotateCW => { game.rotate(true); }
-                    Action::RotateCCW => { game.rotate(false); }
-                    Action::Hold => { game.hold(); }
-                    _ => {}
+        if self.state != GameState::Playing {
+            return;
+        }
+        let Some(game) = &mut self.game else {
+            return;
+        };
+        // Rotation or hold still held when this action spawns the next piece carries over (IRS/IHS)
+        game.spawn_input = self.input.spawn_input();
+
+        let result = match action {
+            Action::MoveLeft => game.move_piece(-1, 0),
+            Action::MoveRight => game.move_piece(1, 0),
+            Action::SoftDrop => game.soft_drop(),
+            Action::HardDrop => game.hard_drop(),
+            Action::RotateCW => game.rotate(Turn::Clockwise),
+            Action::RotateCCW => game.rotate(Turn::CounterClockwise),
+            Action::Rotate180 => game.rotate(Turn::Half),
+            Action::Hold => game.hold(),
+            _ => return,
+        };
+        if game.game_over {
+            self.state = GameState::GameOver;
+        }
+
+        match (action, result) {
+            (Action::HardDrop, _) | (Action::Hold, MoveResult::Success) => {
+                self.reset_piece_timers()
+            }
+            (_, MoveResult::Success) => self.on_piece_moved(),
+            _ => {}
+        }
+        self.begin_delay_after_lock();
+        self.apply_instant_gravity();
+        self.check_goal();
+        self.track_history();
+    }
+
+    /// Keep the game each piece spawned in: once the piece locks, that game is
+    /// what undo goes back to
+    fn track_history(&mut self) {
+        let Some(game) = &self.game else {
+            return;
+        };
+        if let Some(start) = self
+            .piece_start
+            .take_if(|start| start.pieces != game.pieces)
+        {
+            if self.undo_history.len() == MAX_UNDO {
+                self.undo_history.pop_front();
+            }
+            self.undo_history.push_back(start);
+            self.redo_history.clear();
+        }
+        if self.piece_start.is_none() && game.current_piece.is_some() && !game.game_over {
+            self.piece_start = Some(game.clone());
+        }
+    }
+
+    /// Take back the last placed piece: board, queue, hold, randomizer and
+    /// score go back to when it spawned. Works after a game over too, and
+    /// makes the game a practice game.
+    pub fn undo(&mut self) {
+        let Some(game) = self.undo_history.pop_back() else {
+            return;
+        };
+        self.record_input(KEY_UNDO, true);
+        // Redo can only come back to a piece in play
+        match self.piece_start.take() {
+            Some(current) => self.redo_history.push(current),
+            None => self.redo_history.clear(),
+        }
+        self.practice = true;
+        self.restore(game);
+    }
+
+    /// Place again the last piece taken back by `undo`
+    pub fn redo(&mut self) {
+        let Some(game) = self.redo_history.pop() else {
+            return;
+        };
+        self.record_input(KEY_REDO, true);
+        if let Some(current) = self.piece_start.take() {
+            self.undo_history.push_back(current);
+        }
+        self.restore(game);
+    }
+
+    /// Whether the games held by a deserialized controller hold together
+    pub fn is_consistent(&self) -> bool {
+        self.game
+            .iter()
+            .chain(&self.piece_start)
+            .all(Game::is_consistent)
+    }
+
+    fn restore(&mut self, mut game: Game) {
+        // Its events were already taken when they happened
+        game.drain_events();
+        self.piece_start = Some(game.clone());
+        self.game = Some(game);
+        self.state = GameState::Playing;
+        self.phase = Phase::Falling;
+        self.reset_piece_timers();
+    }
+
+    /// Whether undo was used this game, which keeps it off the leaderboard
+    pub fn is_practice(&self) -> bool {
+        self.practice
+    }
+
+    /// End the game as finished once the mode's goal is reached
+    fn check_goal(&mut self) {
+        self.record_sections();
+        let Some(game) = &self.game else {
+            return;
+        };
+        if self.state == GameState::Playing && game.mode().is_complete(game, self.elapsed_ms()) {
+            self.state = GameState::Finished;
+            self.input.reset();
+        }
+    }
+
+    /// Note the play time of each Master section once its last level is passed
+    fn record_sections(&mut self) {
+        let Some(game) = &self.game else {
+            return;
+        };
+        if game.mode() != GameMode::Master {
+            return;
+        }
+        let finished = if game.level >= MASTER_MAX_LEVEL {
+            MASTER_MAX_LEVEL.div_ceil(MASTER_SECTION_LEVELS)
+        } else {
+            game.level / MASTER_SECTION_LEVELS
+        };
+        while self.section_times.len() < finished as usize {
+            let before: f64 = self.section_times.iter().sum();
+            self.section_times.push(self.elapsed_ms() - before);
+        }
+    }
+
+    /// ARE and line-clear delay for the next piece: the Master section's, otherwise `entry_delay`
+    fn current_entry_delay(&self) -> EntryDelay {
+        match &self.game {
+            Some(game) if game.mode() == GameMode::Master => {
+                let delays = master_delays(game.level);
+                EntryDelay {
+                    are_ms: delays.are_frames as f64 * FRAME_MS,
+                    line_clear_ms: delays.line_clear_frames as f64 * FRAME_MS,
                 }
-                if game.game_over {
+            }
+            _ => self.entry_delay,
+        }
+    }
+
+    /// Lock delay for the current piece: the Master section's (reset only by
+    /// falling), otherwise `lock_delay`
+    fn current_lock_delay(&self) -> LockDelay {
+        match &self.game {
+            Some(game) if game.mode() == GameMode::Master => LockDelay {
+                duration_ms: master_delays(game.level).lock_frames as f64 * FRAME_MS,
+                reset: LockReset::NoReset,
+            },
+            _ => self.lock_delay,
+        }
+    }
+
+    /// Current phase between pieces
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Enter the line-clear or entry phase once a lock left no piece in play
+    fn begin_delay_after_lock(&mut self) {
+        let entry_delay = self.current_entry_delay();
+        let Some(game) = &mut self.game else {
+            return;
+        };
+        if self.phase != Phase::Falling || game.game_over || game.current_piece.is_some() {
+            return;
+        }
+        let line_clear_frames = ms_to_frames(entry_delay.line_clear_ms);
+        self.phase = if game.line_clear.is_some() && line_clear_frames > 0 {
+            Phase::LineClear(line_clear_frames)
+        } else {
+            game.line_clear = None;
+            Phase::Entry(ms_to_frames(entry_delay.are_ms))
+        };
+    }
+
+    /// Count down a frame of the line-clear and entry delays, spawning the next piece at the end
+    fn advance_phase(&mut self) {
+        let entry_delay = self.current_entry_delay();
+        let Some(game) = &mut self.game else {
+            return;
+        };
+        match self.phase {
+            Phase::Falling => {}
+            Phase::LineClear(frames) if frames > 1 => self.phase = Phase::LineClear(frames - 1),
+            Phase::LineClear(_) => {
+                game.line_clear = None;
+                self.phase = Phase::Entry(ms_to_frames(entry_delay.are_ms));
+            }
+            Phase::Entry(frames) if frames > 1 => self.phase = Phase::Entry(frames - 1),
+            Phase::Entry(_) => {
+                self.phase = Phase::Falling;
+                game.spawn_input = self.input.spawn_input();
+                if !game.spawn_piece() {
                     self.state = GameState::GameOver;
                 }
+                self.reset_piece_timers();
+                self.apply_instant_gravity();
             }
         }
     }
 
-    /// Update game state (call every frame)
-    /// delta_ms: time since last frame in milliseconds
+    /// 20G: put the piece straight onto the stack whenever it spawns or moves
+    fn apply_instant_gravity(&mut self) {
+        let Some(game) = &mut self.game else {
+            return;
+        };
+        if game.gravity() >= MAX_GRAVITY && game.drop_to_floor() > 0 {
+            self.on_piece_moved();
+        }
+    }
+
+    /// Start gravity and lock delay tracking from scratch for a freshly spawned piece
+    fn reset_piece_timers(&mut self) {
+        self.gravity_counter = 0;
+        self.lock_timer = 0;
+        self.lock_resets = 0;
+        self.lowest_y = self
+            .game
+            .as_ref()
+            .and_then(|g| g.current_piece.as_ref())
+            .map(|p| p.y)
+            .unwrap_or(i32::MAX);
+    }
+
+    /// Apply the lock reset policy after the current piece moved or rotated
+    fn on_piece_moved(&mut self) {
+        let Some(y) = self
+            .game
+            .as_ref()
+            .and_then(|g| g.current_piece.as_ref())
+            .map(|p| p.y)
+        else {
+            return;
+        };
+
+        // Reaching a new lowest row always gives a fresh lock delay
+        if y < self.lowest_y {
+            self.lowest_y = y;
+            self.lock_timer = 0;
+            self.lock_resets = 0;
+            return;
+        }
+
+        // Only moves made while the lock timer is running count as resets
+        if self.lock_timer == 0 {
+            return;
+        }
+        match self.current_lock_delay().reset {
+            LockReset::Infinite => self.lock_timer = 0,
+            LockReset::Step(limit) => {
+                if self.lock_resets < limit {
+                    self.lock_resets += 1;
+                    self.lock_timer = 0;
+                }
+            }
+            LockReset::NoReset => {}
+        }
+    }
+
+    /// Advance by `delta_ms` of real time (call every animation frame). Play runs
+    /// in fixed 60Hz frames; time short of a whole frame carries over to the next call.
     /// Returns true if render state changed
     pub fn update(&mut self, delta_ms: f64) -> bool {
-        if self.state != GameState::Playing {
+        if self.state != GameState::Playing || self.game.is_none() {
             return false;
         }
 
-        if self.game.is_none() {
-            return false;
+        // Kept in 1/60 ms so that whole frames subtract exactly
+        self.frame_time += delta_ms * FRAMES_PER_SECOND;
+        let mut changed = false;
+        while self.frame_time >= 1000.0 && self.state == GameState::Playing {
+            self.frame_time -= 1000.0;
+            self.step_frame();
+            self.track_history();
+            self.feed_replay();
+            changed = true;
+        }
+        changed
+    }
+
+    /// Run one frame of play. Everything here counts whole frames, so the same
+    /// inputs on the same frames always give the same game.
+    fn step_frame(&mut self) {
+        // Play time; a timed mode ends on the frame that reaches its limit
+        self.frames += 1;
+        self.check_goal();
+        if self.state != GameState::Playing {
+            return;
+        }
+
+        // Rising garbage: a row comes up from below every interval
+        if let Some(interval) = self.mode().garbage_rise_ms() {
+            self.garbage_timer += 1;
+            if self.garbage_timer >= ms_to_frames(interval) {
+                self.garbage_timer = 0;
+                if let Some(game) = &mut self.game {
+                    if !game.add_garbage(1) {
+                        self.state = GameState::GameOver;
+                        return;
+                    }
+                }
+            }
         }
 
         // Process DAS/ARR input (collect actions first to avoid borrow issues)
-        let actions: Vec<Action> = self.input.update(delta_ms);
+        let actions: Vec<Action> = self.input.update(FRAME_MS);
         for action in actions {
             self.handle_action(action);
         }
 
-        // Handle gravity with acceleration
-        let (base_interval, acceleration) = {
-            let game = self.game.as_ref().unwrap();
-            (game.get_drop_interval() as f64, game.get_height_acceleration() as f64)
-        };
-        let effective_interval = base_interval / acceleration;
+        // No gravity or lock delay between pieces
+        if self.phase != Phase::Falling {
+            self.advance_phase();
+            return;
+        }
+
+        // Keys held when gravity or lock delay spawns the next piece carry over (IRS/IHS)
+        if let Some(game) = &mut self.game {
+            game.spawn_input = self.input.spawn_input();
+        }
 
-        self.drop_accumulator += delta_ms;
-        if self.drop_accumulator >= effective_interval {
-            self.drop_accumulator = 0.0;
-            if let Some(game) = &mut self.game {
+        let Some(game) = &self.game else {
+            return;
+        };
+        let gravity = game.gravity();
+        let units = gravity_units(gravity) as u64 * game.get_height_acceleration() as u64 / 100;
+        if gravity >= MAX_GRAVITY {
+            // 20G: straight onto the stack rather than a row at a time
+            self.apply_instant_gravity();
+        } else {
+            // One row each time the counter passes a whole row; above 1G that is
+            // several rows per frame
+            self.gravity_counter += units;
+            while self.gravity_counter >= GRAVITY_UNIT as u64 {
+                self.gravity_counter -= GRAVITY_UNIT as u64;
+                let Some(game) = &mut self.game else {
+                    break;
+                };
                 let result = game.tick();
                 if result == MoveResult::GameOver {
                     self.state = GameState::GameOver;
+                    return;
+                }
+                if result != MoveResult::Success {
+                    // Grounded: rows it can't fall are dropped, the fraction of a row is kept
+                    self.gravity_counter %= GRAVITY_UNIT as u64;
+                    break;
                 }
+                self.on_piece_moved();
             }
         }
 
-        true
+        // Lock delay: a grounded piece locks once its timer runs out
+        let grounded = self.game.as_ref().map(|g| g.is_grounded()).unwrap_or(false);
+        if grounded {
+            self.lock_timer += 1;
+            if self.lock_timer >= ms_to_frames(self.current_lock_delay().duration_ms) {
+                if let Some(game) = &mut self.game {
+                    if game.lock_piece() == MoveResult::GameOver {
+                        self.state = GameState::GameOver;
+                    }
+                }
+                self.reset_piece_timers();
+                self.begin_delay_after_lock();
+                self.apply_instant_gravity();
+                self.check_goal();
+            }
+        }
     }
 
     /// Get current render state
     pub fn get_render_state(&self) -> RenderState {
-        match &self.game {
+        let mut state = match &self.game {
             Some(game) => RenderState::from_game(game),
             None => RenderState::default(),
-        }
+        };
+        state.time_remaining_ms = self.time_remaining_ms();
+        state.stats.time_ms = self.elapsed_ms();
+        state
+    }
+
+    /// Totals of the current (or last) game, with its play time
+    pub fn statistics(&self) -> Statistics {
+        let mut stats = self
+            .game
+            .as_ref()
+            .map(|game| game.stats.clone())
+            .unwrap_or_default();
+        stats.time_ms = self.elapsed_ms();
+        stats
+    }
+
+    /// Play time left in a timed mode, frozen while paused
+    pub fn time_remaining_ms(&self) -> Option<f64> {
+        self.mode()
+            .time_limit_ms()
+            .map(|limit| (limit - self.elapsed_ms()).max(0.0))
     }
 
     /// Get current game state as u8
@@ -178,6 +889,7 @@ impl GameController {
             GameState::Playing => 1,
             GameState::Paused => 2,
             GameState::GameOver => 3,
+            GameState::Finished => 4,
         }
     }
 
@@ -196,10 +908,622 @@ impl GameController {
         self.game.as_ref().map(|g| g.lines_cleared).unwrap_or(0)
     }
 
+    /// Play time of the current game in ms, not counting pauses
+    pub fn elapsed_ms(&self) -> f64 {
+        self.frames as f64 * 1000.0 / FRAMES_PER_SECOND
+    }
+
+    /// Frames played in the current game, not counting pauses
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Pieces locked in the current game
+    pub fn pieces(&self) -> u32 {
+        self.game.as_ref().map(|g| g.pieces).unwrap_or(0)
+    }
+
+    /// Garbage rows left on the board
+    pub fn garbage_rows(&self) -> u32 {
+        self.game
+            .as_ref()
+            .map(|g| g.board.garbage_rows())
+            .unwrap_or(0)
+    }
+
+    /// Play time of each Master section finished so far (ms)
+    pub fn section_times(&self) -> &[f64] {
+        &self.section_times
+    }
+
+    /// Master grade the current game has earned so far
+    pub fn grade(&self) -> Option<&'static str> {
+        let game = self.game.as_ref()?;
+        (game.mode() == GameMode::Master)
+            .then(|| master_grade(game.score, game.level, self.elapsed_ms()))
+    }
+
+    /// Pieces locked per second of play time
+    pub fn pps(&self) -> f64 {
+        self.statistics().pps()
+    }
+
+    /// Mode of the current game (or the next one before starting)
+    pub fn mode(&self) -> GameMode {
+        self.game
+            .as_ref()
+            .map(|g| g.mode())
+            .unwrap_or(self.config.mode)
+    }
+
+    /// Scoring ruleset of the current game (or the next one before starting)
+    pub fn scoring(&self) -> ScoringKind {
+        self.game
+            .as_ref()
+            .map(|g| g.scoring())
+            .unwrap_or(self.config.scoring)
+    }
+
+    /// Report whether a perfect clear happened since the last call
+    pub fn take_perfect_clear(&mut self) -> bool {
+        self.game
+            .as_mut()
+            .map(|g| g.take_perfect_clear())
+            .unwrap_or(false)
+    }
+
+    /// Take all events queued since the last call, oldest first
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        let mut events = std::mem::take(&mut self.events);
+        if let Some(game) = &mut self.game {
+            events.extend(game.drain_events());
+        }
+        events
+    }
+
     /// Called when window loses focus
     pub fn on_blur(&mut self) {
         if self.state == GameState::Playing {
-            self.pause();
+            // As the pause key, so the recording sees it
+            self.key_down(7);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::DEFAULT_WIDTH;
+    use crate::gravity::{GravityKind, MAX_GRAVITY};
+    use crate::mode::{DIG_RISE_MS, ULTRA_TIME_MS};
+    use crate::piece::Rotation;
+
+    /// Start a game and drop the first piece onto the floor without locking it
+    fn grounded_controller(reset: LockReset) -> GameController {
+        let mut controller = GameController::new();
+        controller.lock_delay.reset = reset;
+        controller.start();
+        while !controller.game.as_ref().unwrap().is_grounded() {
+            controller.handle_action(Action::SoftDrop);
+        }
+        controller
+    }
+
+    fn floor_is_empty(controller: &GameController) -> bool {
+        let board = &controller.game.as_ref().unwrap().board;
+        (0..DEFAULT_WIDTH as i32).all(|x| board.get(x, 0).unwrap().is_empty())
+    }
+
+    /// Tap left or right, alternating so the piece never hits a wall
+    fn shift(controller: &mut GameController, step: usize) {
+        let key = (step % 2) as u8;
+        controller.key_down(key);
+        controller.key_up(key);
+    }
+
+    fn start_with_i_pieces(controller: &mut GameController) {
+        let script = crate::randomizer::ScriptedRandomizer::parse("I").unwrap();
+        controller.config.randomizer = crate::randomizer::PieceRandomizer::Scripted(script);
+        controller.start();
+    }
+
+    /// Fill the floor except below the current flat I piece, so a hard drop clears it
+    fn fill_floor_around_piece(controller: &mut GameController) {
+        let game = controller.game.as_mut().unwrap();
+        let piece = game.current_piece.as_ref().unwrap();
+        let columns: Vec<i32> = piece.get_blocks().iter().map(|b| b.0).collect();
+        for x in 0..DEFAULT_WIDTH as i32 {
+            if !columns.contains(&x) {
+                game.board.set(x, 0, crate::board::Cell::O);
+            }
+        }
+    }
+
+    #[test]
+    fn test_piece_waits_for_lock_delay() {
+        let mut controller = grounded_controller(LockReset::Infinite);
+
+        controller.update(LOCK_DELAY_MS - 100.0);
+        assert!(floor_is_empty(&controller));
+
+        controller.update(200.0);
+        assert!(!floor_is_empty(&controller));
+        assert_eq!(controller.state, GameState::Playing);
+    }
+
+    #[test]
+    fn test_infinite_reset_never_locks_while_moving() {
+        let mut controller = grounded_controller(LockReset::Infinite);
+
+        for step in 0..40 {
+            controller.update(LOCK_DELAY_MS - 100.0);
+            shift(&mut controller, step);
+        }
+        assert!(floor_is_empty(&controller));
+    }
+
+    #[test]
+    fn test_step_reset_is_capped() {
+        let mut controller = grounded_controller(LockReset::Step(LOCK_RESET_LIMIT));
+
+        for step in 0..LOCK_RESET_LIMIT as usize {
+            controller.update(LOCK_DELAY_MS - 100.0);
+            shift(&mut controller, step);
         }
+        assert!(floor_is_empty(&controller));
+
+        // Resets are used up, so this move no longer restarts the timer
+        controller.update(LOCK_DELAY_MS - 100.0);
+        shift(&mut controller, 0);
+        controller.update(200.0);
+        assert!(!floor_is_empty(&controller));
+    }
+
+    #[test]
+    fn test_no_reset_locks_despite_moving() {
+        let mut controller = grounded_controller(LockReset::NoReset);
+
+        controller.update(LOCK_DELAY_MS - 100.0);
+        shift(&mut controller, 0);
+        controller.update(200.0);
+        assert!(!floor_is_empty(&controller));
+    }
+
+    #[test]
+    fn test_new_lowest_row_restarts_timer() {
+        let mut controller = GameController::new();
+        controller.lock_delay.reset = LockReset::NoReset;
+        controller.start();
+
+        // Let the timer run on a raised floor, then remove the floor under the piece
+        let game = controller.game.as_mut().unwrap();
+        for x in 0..DEFAULT_WIDTH as i32 {
+            game.board.set(x, 0, crate::board::Cell::O);
+        }
+        while !controller.game.as_ref().unwrap().is_grounded() {
+            controller.handle_action(Action::SoftDrop);
+        }
+        controller.update(LOCK_DELAY_MS - 100.0);
+
+        let game = controller.game.as_mut().unwrap();
+        for x in 0..DEFAULT_WIDTH as i32 {
+            game.board.set(x, 0, crate::board::Cell::Empty);
+        }
+        controller.handle_action(Action::SoftDrop);
+
+        controller.update(LOCK_DELAY_MS - 100.0);
+        assert!(floor_is_empty(&controller));
+    }
+
+    #[test]
+    fn test_held_keys_apply_at_spawn() {
+        let mut controller = GameController::new();
+        controller.start_with_seed(7);
+        let game = controller.game.as_ref().unwrap();
+        let first = game.current_piece.as_ref().unwrap().piece_type;
+        let queue = game.peek_queue(2);
+
+        // C holds the first piece. Keeping C and X down through a hard drop holds the
+        // piece spawning next and brings the first one back already rotated.
+        controller.key_down(6);
+        controller.key_down(4);
+        controller.key_down(3);
+        let game = controller.game.as_ref().unwrap();
+        assert_eq!(game.hold_piece, Some(queue[1]));
+        let piece = game.current_piece.as_ref().unwrap();
+        assert_eq!((piece.piece_type, piece.rotation), (first, Rotation::R90));
+
+        // Released keys don't carry over
+        controller.key_up(6);
+        controller.key_up(4);
+        controller.key_down(3);
+        let game = controller.game.as_ref().unwrap();
+        assert_eq!(game.current_piece.as_ref().unwrap().rotation, Rotation::R0);
+    }
+
+    #[test]
+    fn test_entry_delay_holds_next_piece() {
+        let mut controller = GameController::new();
+        controller.entry_delay = EntryDelay {
+            are_ms: 100.0,
+            line_clear_ms: 0.0,
+        };
+        controller.start();
+        controller.key_down(3);
+        assert!(controller.game.as_ref().unwrap().current_piece.is_none());
+        assert_eq!(controller.phase(), Phase::Entry(6));
+
+        controller.update(60.0);
+        assert!(controller.game.as_ref().unwrap().current_piece.is_none());
+        controller.update(60.0);
+        assert!(controller.game.as_ref().unwrap().current_piece.is_some());
+        assert_eq!(controller.phase(), Phase::Falling);
+    }
+
+    #[test]
+    fn test_cleared_rows_shown_during_line_clear_delay() {
+        let mut controller = GameController::new();
+        controller.entry_delay = EntryDelay {
+            are_ms: 0.0,
+            line_clear_ms: 200.0,
+        };
+        start_with_i_pieces(&mut controller);
+        fill_floor_around_piece(&mut controller);
+        controller.key_down(3);
+        assert_eq!(controller.game.as_ref().unwrap().lines_cleared, 1);
+
+        let bottom = controller.get_render_state().height - 1;
+        let state = controller.get_render_state();
+        assert_eq!(state.clearing_rows, vec![bottom]);
+        let faded = state
+            .board_cells
+            .iter()
+            .filter(|c| c.y == bottom && c.opacity < 255);
+        assert_eq!(faded.count(), DEFAULT_WIDTH);
+
+        controller.update(150.0);
+        assert_eq!(controller.get_render_state().clearing_rows, vec![bottom]);
+        controller.update(50.0);
+        assert!(controller.get_render_state().clearing_rows.is_empty());
+        assert!(controller.game.as_ref().unwrap().current_piece.is_none());
+        controller.update(FRAME_MS);
+        assert!(controller.game.as_ref().unwrap().current_piece.is_some());
+    }
+
+    #[test]
+    fn test_same_frames_whatever_the_frame_rate() {
+        let play = |deltas: &[f64]| {
+            let mut controller = GameController::new();
+            controller.start_with_seed(11);
+            controller.key_down(0);
+            for &delta in deltas.iter().cycle().take(2000) {
+                controller.update(delta);
+            }
+            let game = controller.game.as_ref().unwrap();
+            (
+                controller.frames(),
+                game.pieces,
+                game.score,
+                game.board.get_visible_grid(),
+            )
+        };
+        // 32 s either way: 60Hz, 144Hz-like short frames mixed with long ones
+        let smooth = play(&[16.0]);
+        let uneven = play(&[5.0, 27.0]);
+        assert_eq!(smooth.0, 1920);
+        assert!(smooth.1 > 0);
+        assert_eq!(smooth, uneven);
+    }
+
+    #[test]
+    fn test_replay_plays_the_same_game() {
+        let mut controller = GameController::new();
+        controller.entry_delay = EntryDelay::nes();
+        controller.start();
+        for step in 0..600u32 {
+            match step % 40 {
+                3 => controller.key_down(step as u8 / 40 % 2),
+                9 => controller.key_up(step as u8 / 40 % 2),
+                12 => controller.key_down(4),
+                13 => controller.key_up(4),
+                20 => controller.key_down(3),
+                21 => controller.key_up(3),
+                _ => {}
+            }
+            if step == 300 {
+                controller.on_blur();
+                controller.update(500.0);
+                controller.key_down(7);
+            }
+            controller.update(16.0);
+        }
+        let snapshot = |controller: &GameController| {
+            let game = controller.game.as_ref().unwrap();
+            (game.pieces, game.score, game.board.get_visible_grid())
+        };
+        let played = snapshot(&controller);
+        let (frames, state) = (controller.frames(), controller.state);
+        let replay = Replay::from_bytes(&controller.replay().unwrap().to_bytes()).unwrap();
+        assert!(played.0 > 5);
+
+        controller.play_replay(&replay);
+        assert!(controller.is_replaying());
+        // Moves by the viewer are ignored
+        controller.key_down(1);
+        while controller.frames() < frames && controller.state == GameState::Playing {
+            controller.update(FRAME_MS);
+        }
+        assert_eq!(controller.frames(), frames);
+        assert_eq!(controller.state, state);
+        assert_eq!(snapshot(&controller), played);
+        assert_eq!(controller.replay(), Some(&replay));
+
+        // A new game goes back to the player's own settings
+        controller.entry_delay = EntryDelay::guideline();
+        controller.start();
+        assert!(!controller.is_replaying());
+        assert_eq!(controller.entry_delay, EntryDelay::nes());
+    }
+
+    #[test]
+    fn test_20g_spawns_on_the_stack() {
+        let mut controller = GameController::new();
+        controller.config.gravity = GravityKind::Table(vec![MAX_GRAVITY]);
+        controller.start();
+        assert!(controller.game.as_ref().unwrap().is_grounded());
+
+        // The next piece lands as it spawns, without waiting for a frame
+        controller.key_down(3);
+        assert!(controller.game.as_ref().unwrap().is_grounded());
+        controller.update(1000.0 / 60.0);
+        assert!(controller.game.as_ref().unwrap().is_grounded());
+    }
+
+    #[test]
+    fn test_undo_and_redo_placements() {
+        let mut controller = GameController::new();
+        controller.start_with_seed(3);
+        let spawned = |controller: &GameController| {
+            let game = controller.game.as_ref().unwrap();
+            let piece = game.current_piece.as_ref().map(|p| p.piece_type);
+            (
+                game.pieces,
+                game.score,
+                piece,
+                game.board.get_visible_grid(),
+            )
+        };
+        let mut states = vec![spawned(&controller)];
+        for _ in 0..3 {
+            controller.key_down(3);
+            controller.key_up(3);
+            states.push(spawned(&controller));
+        }
+        assert!(!controller.is_practice());
+
+        controller.key_down(KEY_UNDO);
+        assert_eq!(spawned(&controller), states[2]);
+        controller.key_down(KEY_UNDO);
+        assert_eq!(spawned(&controller), states[1]);
+        assert!(controller.is_practice());
+        controller.key_down(KEY_REDO);
+        assert_eq!(spawned(&controller), states[2]);
+
+        // Placing another piece drops what could be redone
+        controller.key_down(3);
+        controller.key_down(KEY_REDO);
+        assert_eq!(controller.game.as_ref().unwrap().pieces, 3);
+
+        // Undo takes back the piece that topped out
+        while controller.state == GameState::Playing {
+            controller.key_down(3);
+        }
+        let pieces = controller.game.as_ref().unwrap().pieces;
+        controller.key_down(KEY_UNDO);
+        assert_eq!(controller.state, GameState::Playing);
+        assert_eq!(controller.game.as_ref().unwrap().pieces, pieces - 1);
+
+        controller.start();
+        assert!(!controller.is_practice());
+    }
+
+    #[test]
+    fn test_replay_of_undo_after_game_over() {
+        let mut controller = GameController::new();
+        controller.start_with_seed(4);
+        while controller.state == GameState::Playing {
+            controller.key_down(3);
+            controller.key_up(3);
+            controller.update(50.0);
+        }
+        controller.key_down(KEY_UNDO);
+        controller.key_up(KEY_UNDO);
+        assert_eq!(controller.state, GameState::Playing);
+        // Redo with nothing undone does nothing, so it isn't recorded
+        controller.key_down(KEY_REDO);
+        for _ in 0..3 {
+            controller.key_down(0);
+            controller.update(200.0);
+            controller.key_up(0);
+            controller.update(200.0);
+        }
+        let played = {
+            let game = controller.game.as_ref().unwrap();
+            (
+                game.pieces,
+                game.board.get_visible_grid(),
+                controller.frames(),
+            )
+        };
+        let replay = Replay::from_bytes(&controller.replay().unwrap().to_bytes()).unwrap();
+        let keys: Vec<u8> = replay.inputs.iter().map(|input| input.key).collect();
+        assert_eq!(keys.iter().filter(|&&key| key == 11).count(), 1);
+        assert!(!keys.contains(&12));
+
+        controller.play_replay(&replay);
+        while controller.frames() < played.2 && controller.state == GameState::Playing {
+            controller.update(FRAME_MS);
+        }
+        let game = controller.game.as_ref().unwrap();
+        assert_eq!(
+            (
+                game.pieces,
+                game.board.get_visible_grid(),
+                controller.frames()
+            ),
+            played
+        );
+        assert!(controller.is_practice());
+        assert_eq!(controller.replay(), Some(&replay));
+    }
+
+    #[test]
+    fn test_statistics_count_key_presses_and_locks() {
+        let mut controller = GameController::new();
+        controller.start_with_seed(9);
+        // Held long enough to auto-repeat, still one key
+        controller.key_down(0);
+        controller.update(500.0);
+        controller.key_up(0);
+        controller.key_down(4);
+        controller.key_down(3);
+        controller.key_down(3);
+        controller.update(1000.0);
+
+        let stats = controller.statistics();
+        assert_eq!(stats.pieces, 2);
+        assert_eq!(stats.keys, 4);
+        assert_eq!(stats.kpp(), 2.0);
+        assert_eq!(stats.piece_counts.iter().sum::<u32>(), 2);
+        assert_eq!(stats.time_ms, controller.elapsed_ms());
+        assert_eq!(controller.get_render_state().stats, stats);
+
+        // Undo takes back the piece's counts too
+        controller.key_down(KEY_UNDO);
+        assert_eq!(controller.statistics().pieces, 1);
+    }
+
+    #[test]
+    fn test_restart_keeps_start_level() {
+        let mut controller = GameController::new();
+        controller.start_at_level(8);
+        assert_eq!(controller.get_level(), 8);
+        controller.restart();
+        assert_eq!(controller.get_level(), 8);
+    }
+
+    #[test]
+    fn test_sprint_finishes_at_line_goal() {
+        let mut controller = GameController::new();
+        controller.config.mode = GameMode::Sprint { lines: 20 };
+        start_with_i_pieces(&mut controller);
+        controller.update(250.0);
+
+        // Paused time doesn't count
+        controller.key_down(7);
+        controller.update(10_000.0);
+        controller.key_down(7);
+        controller.update(250.0);
+
+        controller.game.as_mut().unwrap().lines_cleared = 19;
+        fill_floor_around_piece(&mut controller);
+        controller.key_down(3);
+        assert_eq!(controller.state, GameState::Finished);
+        assert_eq!(controller.elapsed_ms(), 500.0);
+        assert_eq!(controller.pieces(), 1);
+        assert_eq!(controller.pps(), 2.0);
+
+        // The clock stops at the finish
+        controller.update(100.0);
+        assert_eq!(controller.elapsed_ms(), 500.0);
+        controller.key_down(9);
+        assert_eq!(controller.state, GameState::Playing);
+        assert_eq!(controller.elapsed_ms(), 0.0);
+    }
+
+    #[test]
+    fn test_ultra_ends_when_time_runs_out() {
+        let mut controller = GameController::new();
+        controller.config.mode = GameMode::ultra();
+        // Slow enough that the first piece is still falling at the end
+        controller.config.gravity = GravityKind::Table(vec![0.0001]);
+        controller.start();
+        controller.update(60_000.0);
+        assert_eq!(controller.time_remaining_ms(), Some(60_000.0));
+
+        // The clock is frozen while paused
+        controller.key_down(7);
+        controller.update(90_000.0);
+        assert_eq!(
+            controller.get_render_state().time_remaining_ms,
+            Some(60_000.0)
+        );
+        controller.key_down(7);
+
+        controller.update(59_999.0);
+        assert_eq!(controller.state, GameState::Playing);
+        controller.update(16.0);
+        assert_eq!(controller.state, GameState::Finished);
+        assert_eq!(controller.time_remaining_ms(), Some(0.0));
+        assert_eq!(controller.elapsed_ms(), ULTRA_TIME_MS as f64);
+    }
+
+    #[test]
+    fn test_dig_finishes_when_garbage_is_cleared() {
+        let mut controller = GameController::new();
+        controller.config.seed = Some(1);
+        controller.config.mode = GameMode::dig(5, true);
+        controller.start();
+        assert_eq!(controller.game.as_ref().unwrap().board.garbage_rows(), 5);
+
+        controller.update(DIG_RISE_MS as f64);
+        assert_eq!(controller.game.as_ref().unwrap().board.garbage_rows(), 6);
+
+        controller.game.as_mut().unwrap().board = crate::board::Board::new();
+        controller.key_down(3);
+        assert_eq!(controller.state, GameState::Finished);
+    }
+
+    #[test]
+    fn test_master_sections_and_grade() {
+        let mut controller = GameController::new();
+        controller.config.mode = GameMode::Master;
+        start_with_i_pieces(&mut controller);
+        assert_eq!(controller.get_level(), 0);
+        assert!(controller.game.as_ref().unwrap().is_grounded());
+        assert_eq!(controller.grade(), Some("9"));
+
+        // A line clear gets past level 99, finishing the first section
+        controller.update(400.0);
+        controller.game.as_mut().unwrap().level = 99;
+        fill_floor_around_piece(&mut controller);
+        controller.key_down(3);
+        assert_eq!(controller.get_level(), 100);
+        assert_eq!(controller.section_times(), &[400.0]);
+        assert_eq!(controller.phase(), Phase::LineClear(40));
+    }
+
+    #[test]
+    fn test_restart_keeps_undrained_events() {
+        let mut controller = GameController::new();
+        controller.start();
+        controller.key_down(3);
+        controller.restart();
+
+        let codes: Vec<i32> = controller.drain_events().iter().map(|e| e.code()).collect();
+        // Spawn, lock, spawn from the first game, then the new game's first spawn
+        assert_eq!(codes, vec![0, 1, 0, 0]);
+        assert!(controller.drain_events().is_empty());
+    }
+
+    #[test]
+    fn test_restart_reuses_seed() {
+        let mut controller = GameController::new();
+        controller.start_with_seed(99);
+        let first = controller.game.as_ref().unwrap().next_piece();
+        controller.start();
+        let game = controller.game.as_ref().unwrap();
+        assert_eq!(game.seed, 99);
+        assert_eq!(game.next_piece(), first);
     }
 }
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+
+/// Frame rate that gravity (rows per frame) is measured against
+pub const FRAMES_PER_SECOND: f64 = 60.0;
+/// 20G: the piece falls to the floor as soon as it appears
+pub const MAX_GRAVITY: f64 = 20.0;
+/// Lines per level-up on the default progression
+pub const LINES_PER_LEVEL: u32 = 10;
+/// Gravity counter units per row: a piece falls a row each time its counter passes this
+pub const GRAVITY_UNIT: u32 = 65536;
+
+/// NES frames per row for NES levels 0-29 (level 29 and up is the kill screen speed)
+const NES_FRAMES: [u32; 30] = [
+    48, 43, 38, 33, 28, 23, 18, 13, 8, 6, 5, 5, 5, 4, 4, 4, 3, 3, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    1,
+];
+
+/// Falling speed by level, in G (rows per frame at 60 fps):
+/// 1/60 G is one row per second, 20G drops the piece instantly
+pub trait GravityCurve {
+    /// Gravity at `level` (1 = first level)
+    fn gravity(&self, level: u32) -> f64;
+
+    /// Level after `lines` cleared lines in a game started at `start_level`
+    fn level(&self, start_level: u32, lines: u32) -> u32 {
+        start_level + lines / LINES_PER_LEVEL
+    }
+}
+
+/// Guideline formula: (0.8 - (L-1) × 0.007)^(L-1) seconds per row
+pub struct Guideline;
+
+impl GravityCurve for Guideline {
+    fn gravity(&self, level: u32) -> f64 {
+        let steps = level.saturating_sub(1) as f64;
+        let seconds = (0.8 - steps * 0.007).powf(steps);
+        if seconds > 0.0 {
+            (1.0 / (seconds * FRAMES_PER_SECOND)).min(MAX_GRAVITY)
+        } else {
+            MAX_GRAVITY
+        }
+    }
+}
+
+/// NES frame table, where level 1 here is NES level 0.
+/// Starting above level 1 delays the first level-up as on the NES: it comes
+/// after min(S × 10 + 10, max(100, S × 10 - 50)) lines for NES start level S,
+/// then every 10 lines.
+pub struct Nes;
+
+impl GravityCurve for Nes {
+    fn gravity(&self, level: u32) -> f64 {
+        let index = (level.saturating_sub(1) as usize).min(NES_FRAMES.len() - 1);
+        1.0 / NES_FRAMES[index] as f64
+    }
+
+    fn level(&self, start_level: u32, lines: u32) -> u32 {
+        let start = start_level.saturating_sub(1);
+        let first = (start * 10 + 10).min((start * 10).saturating_sub(50).max(100));
+        if lines < first {
+            start_level
+        } else {
+            start_level + 1 + (lines - first) / LINES_PER_LEVEL
+        }
+    }
+}
+
+/// The built-in gravity curves plus user tables, chosen when a game is constructed
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawGravityKind")]
+pub enum GravityKind {
+    Guideline,
+    #[default]
+    Nes,
+    /// Gravity in G per level, starting at level 1; levels past the end keep the last value
+    Table(Vec<f64>),
+}
+
+/// Deserialized form of `GravityKind`; tables go through `GravityKind::table`
+#[derive(Deserialize)]
+enum RawGravityKind {
+    Guideline,
+    Nes,
+    Table(Vec<f64>),
+}
+
+impl TryFrom<RawGravityKind> for GravityKind {
+    type Error = &'static str;
+
+    fn try_from(raw: RawGravityKind) -> Result<Self, Self::Error> {
+        match raw {
+            RawGravityKind::Guideline => Ok(GravityKind::Guideline),
+            RawGravityKind::Nes => Ok(GravityKind::Nes),
+            RawGravityKind::Table(table) => {
+                GravityKind::table(&table).ok_or("gravity table must be non-empty and positive")
+            }
+        }
+    }
+}
+
+impl GravityKind {
+    /// Frontend selector for the built-in curves: 0=Guideline, 1=NES
+    pub fn from_u8(kind: u8) -> Option<Self> {
+        match kind {
+            0 => Some(GravityKind::Guideline),
+            1 => Some(GravityKind::Nes),
+            _ => None,
+        }
+    }
+
+    /// User table of gravity per level. Values are clamped to 20G; `None` if the
+    /// table is empty or has a value that isn't positive.
+    pub fn table(gravity: &[f64]) -> Option<Self> {
+        if gravity.is_empty() || gravity.iter().any(|g| g.is_nan() || *g <= 0.0) {
+            return None;
+        }
+        Some(GravityKind::Table(
+            gravity.iter().map(|g| g.min(MAX_GRAVITY)).collect(),
+        ))
+    }
+}
+
+impl GravityCurve for GravityKind {
+    fn gravity(&self, level: u32) -> f64 {
+        match self {
+            GravityKind::Guideline => Guideline.gravity(level),
+            GravityKind::Nes => Nes.gravity(level),
+            GravityKind::Table(table) => {
+                let index = level.saturating_sub(1) as usize;
+                table[index.min(table.len() - 1)]
+            }
+        }
+    }
+
+    fn level(&self, start_level: u32, lines: u32) -> u32 {
+        match self {
+            GravityKind::Nes => Nes.level(start_level, lines),
+            _ => start_level + lines / LINES_PER_LEVEL,
+        }
+    }
+}
+
+/// Milliseconds per row at `gravity` G (under one frame above 1G)
+pub fn drop_interval_ms(gravity: f64) -> f64 {
+    1000.0 / (gravity * FRAMES_PER_SECOND)
+}
+
+/// `gravity` G in counter units per frame. Rounded up, so a piece that should
+/// take N frames per row falls on the Nth frame rather than one late.
+pub fn gravity_units(gravity: f64) -> u32 {
+    (gravity * GRAVITY_UNIT as f64).ceil() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guideline_formula() {
+        // Level 1 is one row per second
+        assert!((Guideline.gravity(1) - 1.0 / 60.0).abs() < 1e-9);
+        // Level 15 is about 7 ms per row
+        let seconds = 0.702f64.powi(14);
+        assert!((Guideline.gravity(15) - 1.0 / (seconds * 60.0)).abs() < 1e-9);
+        assert_eq!(Guideline.gravity(200), MAX_GRAVITY);
+    }
+
+    #[test]
+    fn test_nes_frames() {
+        assert_eq!(Nes.gravity(1), 1.0 / 48.0);
+        assert_eq!(Nes.gravity(11), 1.0 / 5.0);
+        assert_eq!(Nes.gravity(30), 1.0);
+        assert_eq!(Nes.gravity(99), 1.0);
+    }
+
+    #[test]
+    fn test_nes_start_level_transition() {
+        // Start at NES level 0: every 10 lines
+        assert_eq!(Nes.level(1, 9), 1);
+        assert_eq!(Nes.level(1, 10), 2);
+        // NES level 9: first level-up after 100 lines
+        assert_eq!(Nes.level(10, 99), 10);
+        assert_eq!(Nes.level(10, 100), 11);
+        assert_eq!(Nes.level(10, 110), 12);
+        // NES level 18: after 130 lines
+        assert_eq!(Nes.level(19, 129), 19);
+        assert_eq!(Nes.level(19, 130), 20);
+        // NES level 5: after 60 lines
+        assert_eq!(Nes.level(6, 59), 6);
+        assert_eq!(Nes.level(6, 60), 7);
+    }
+
+    #[test]
+    fn test_user_table() {
+        let table = GravityKind::table(&[0.05, 1.0, 40.0]).unwrap();
+        assert_eq!(table.gravity(1), 0.05);
+        assert_eq!(table.gravity(3), MAX_GRAVITY);
+        assert_eq!(table.gravity(10), MAX_GRAVITY);
+        assert_eq!(table.level(3, 25), 5);
+        assert!(GravityKind::table(&[]).is_none());
+        assert!(GravityKind::table(&[1.0, 0.0]).is_none());
+        assert!(GravityKind::table(&[f64::NAN]).is_none());
+
+        let json = serde_json::to_string(&table).unwrap();
+        assert_eq!(serde_json::from_str::<GravityKind>(&json).ok(), Some(table));
+        assert_eq!(
+            serde_json::from_str::<GravityKind>(r#""Nes""#).ok(),
+            Some(GravityKind::Nes)
+        );
+        assert!(serde_json::from_str::<GravityKind>(r#"{"Table":[]}"#).is_err());
+        assert!(serde_json::from_str::<GravityKind>(r#"{"Table":[1.0,-1.0]}"#).is_err());
+    }
+
+    #[test]
+    fn test_gravity_units_keep_whole_frame_rates() {
+        for frames in NES_FRAMES {
+            let units = gravity_units(1.0 / frames as f64);
+            assert!(units * (frames - 1) < GRAVITY_UNIT);
+            assert!(units * frames >= GRAVITY_UNIT);
+        }
+        assert_eq!(gravity_units(MAX_GRAVITY), 20 * GRAVITY_UNIT);
+    }
+}
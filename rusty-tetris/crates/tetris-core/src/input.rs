@@ -1,3 +1,5 @@
+use crate::game::SpawnInput;
+use crate::piece::Turn;
 use serde::{Deserialize, Serialize};
 
 /// Input actions
@@ -9,10 +11,30 @@ pub enum Action {
     HardDrop,
     RotateCW,
     RotateCCW,
+    Rotate180,
     Hold,
     Pause,
     Start,
     Restart,
+    Undo,
+    Redo,
+}
+
+impl Action {
+    /// Whether the action moves, turns, drops or holds the piece in play
+    pub fn acts_on_piece(self) -> bool {
+        matches!(
+            self,
+            Action::MoveLeft
+                | Action::MoveRight
+                | Action::SoftDrop
+                | Action::HardDrop
+                | Action::RotateCW
+                | Action::RotateCCW
+                | Action::Rotate180
+                | Action::Hold
+        )
+    }
 }
 
 /// DAS/ARR input handler
@@ -27,6 +49,9 @@ pub struct InputState {
     left_held: bool,
     right_held: bool,
     down_held: bool,
+    // Rotation and hold keys, buffered for IRS/IHS
+    rotate_held: Option<Turn>,
+    hold_held: bool,
 
     // DAS timers (time held in ms)
     left_time: f64,
@@ -53,6 +78,8 @@ impl InputState {
             left_held: false,
             right_held: false,
             down_held: false,
+            rotate_held: None,
+            hold_held: false,
             left_time: 0.0,
             right_time: 0.0,
             down_time: 0.0,
@@ -64,37 +91,49 @@ impl InputState {
 
     pub fn key_down(&mut self, key: u8) -> Option<Action> {
         match key {
-            0 => { // Left
-                if !self.left_held {
-                    self.left_held = true;
-                    self.left_time = 0.0;
-                    self.left_arr_time = 0.0;
-                    return Some(Action::MoveLeft);
-                }
+            // Left
+            0 if !self.left_held => {
+                self.left_held = true;
+                self.left_time = 0.0;
+                self.left_arr_time = 0.0;
+                return Some(Action::MoveLeft);
             }
-            1 => { // Right
-                if !self.right_held {
-                    self.right_held = true;
-                    self.right_time = 0.0;
-                    self.right_arr_time = 0.0;
-                    return Some(Action::MoveRight);
-                }
+            // Right
+            1 if !self.right_held => {
+                self.right_held = true;
+                self.right_time = 0.0;
+                self.right_arr_time = 0.0;
+                return Some(Action::MoveRight);
             }
-            2 => { // Down
-                if !self.down_held {
-                    self.down_held = true;
-                    self.down_time = 0.0;
-                    self.down_arr_time = 0.0;
-                    return Some(Action::SoftDrop);
-                }
+            // Down
+            2 if !self.down_held => {
+                self.down_held = true;
+                self.down_time = 0.0;
+                self.down_arr_time = 0.0;
+                return Some(Action::SoftDrop);
             }
             3 => return Some(Action::HardDrop),
-            4 => return Some(Action::RotateCW),
-            5 => return Some(Action::RotateCCW),
-            6 => return Some(Action::Hold),
+            4 => {
+                self.rotate_held = Some(Turn::Clockwise);
+                return Some(Action::RotateCW);
+            }
+            5 => {
+                self.rotate_held = Some(Turn::CounterClockwise);
+                return Some(Action::RotateCCW);
+            }
+            6 => {
+                self.hold_held = true;
+                return Some(Action::Hold);
+            }
             7 => return Some(Action::Pause),
             8 => return Some(Action::Start),
             9 => return Some(Action::Restart),
+            10 => {
+                self.rotate_held = Some(Turn::Half);
+                return Some(Action::Rotate180);
+            }
+            11 => return Some(Action::Undo),
+            12 => return Some(Action::Redo),
             _ => {}
         }
         None
@@ -105,10 +144,28 @@ impl InputState {
             0 => self.left_held = false,
             1 => self.right_held = false,
             2 => self.down_held = false,
+            4 => self.release_rotation(Turn::Clockwise),
+            5 => self.release_rotation(Turn::CounterClockwise),
+            6 => self.hold_held = false,
+            10 => self.release_rotation(Turn::Half),
             _ => {}
         }
     }
 
+    fn release_rotation(&mut self, turn: Turn) {
+        if self.rotate_held == Some(turn) {
+            self.rotate_held = None;
+        }
+    }
+
+    /// Rotation and hold keys currently held, to apply to the next piece as it spawns
+    pub fn spawn_input(&self) -> SpawnInput {
+        SpawnInput {
+            rotation: self.rotate_held,
+            hold: self.hold_held,
+        }
+    }
+
     /// Update DAS/ARR timers, returns actions to execute
     pub fn update(&mut self, delta_ms: f64) -> Vec<Action> {
         let mut actions = Vec::new();
@@ -156,6 +213,8 @@ impl InputState {
         self.left_held = false;
         self.right_held = false;
         self.down_held = false;
+        self.rotate_held = None;
+        self.hold_held = false;
         self.left_time = 0.0;
         self.right_time = 0.0;
         self.down_time = 0.0;
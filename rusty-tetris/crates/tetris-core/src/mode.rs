@@ -0,0 +1,266 @@
+use crate::game::Game;
+use serde::{Deserialize, Serialize};
+
+/// Line goals offered for Sprint
+pub const SPRINT_GOALS: [u32; 3] = [20, 40, 100];
+/// Sprint line goal unless another is chosen
+pub const DEFAULT_SPRINT_LINES: u32 = 40;
+/// Ultra play time
+pub const ULTRA_TIME_MS: u32 = 2 * 60 * 1000;
+/// Starting garbage rows offered for Dig
+pub const DIG_ROWS: [u32; 3] = [5, 10, 18];
+/// Dig garbage rows unless another count is chosen
+pub const DEFAULT_DIG_ROWS: u32 = 10;
+/// Play time between rising garbage rows in Dig
+pub const DIG_RISE_MS: u32 = 5000;
+/// Master ends on reaching this level
+pub const MASTER_MAX_LEVEL: u32 = 999;
+/// Master levels per section
+pub const MASTER_SECTION_LEVELS: u32 = 100;
+/// Master score grades from 9 up to S9, with the score each one needs (TGM)
+const MASTER_GRADES: [(&str, u32); 18] = [
+    ("9", 0),
+    ("8", 400),
+    ("7", 800),
+    ("6", 1400),
+    ("5", 2000),
+    ("4", 3500),
+    ("3", 5500),
+    ("2", 8000),
+    ("1", 12000),
+    ("S1", 16000),
+    ("S2", 22000),
+    ("S3", 30000),
+    ("S4", 40000),
+    ("S5", 52000),
+    ("S6", 66000),
+    ("S7", 82000),
+    ("S8", 100000),
+    ("S9", 120000),
+];
+/// Grand Master: reach level 999 with this score within this time
+const MASTER_GM_SCORE: u32 = 126000;
+const MASTER_GM_TIME_MS: f64 = (13 * 60 + 30) as f64 * 1000.0;
+/// Master delays per section in frames: ARE, line clear, lock delay (after TGM2 Master)
+const MASTER_DELAYS: [(u32, u32, u32); 10] = [
+    (25, 40, 30),
+    (25, 40, 30),
+    (25, 40, 30),
+    (25, 40, 30),
+    (25, 40, 30),
+    (25, 25, 30),
+    (25, 16, 30),
+    (16, 12, 30),
+    (12, 6, 30),
+    (12, 6, 17),
+];
+
+/// What a game is played for
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    /// Endless: play until topping out
+    #[default]
+    Marathon,
+    /// Clear `lines` lines as fast as possible
+    Sprint { lines: u32 },
+    /// Score as much as possible in `time_ms` of play
+    Ultra { time_ms: u32 },
+    /// Start above `rows` rows of garbage and clear all of it. With `rise_ms`,
+    /// another row comes up from below every `rise_ms` of play.
+    Dig { rows: u32, rise_ms: Option<u32> },
+    /// 20G from the first piece to level 999, with delays shrinking every
+    /// section of 100 levels, graded on score, level and time
+    Master,
+}
+
+impl GameMode {
+    pub fn sprint() -> Self {
+        GameMode::Sprint {
+            lines: DEFAULT_SPRINT_LINES,
+        }
+    }
+
+    pub fn ultra() -> Self {
+        GameMode::Ultra {
+            time_ms: ULTRA_TIME_MS,
+        }
+    }
+
+    /// Dig with `rows` garbage rows, rising every `DIG_RISE_MS` if `rising`
+    pub fn dig(rows: u32, rising: bool) -> Self {
+        GameMode::Dig {
+            rows,
+            rise_ms: rising.then_some(DIG_RISE_MS),
+        }
+    }
+
+    /// Frontend selector: 0=Marathon, 1=Sprint, 2=Ultra, 3=Dig, 4=Dig with
+    /// rising garbage, 5=Master. `goal` is the Sprint line goal (20, 40 or 100) or the Dig
+    /// garbage rows (5, 10 or 18), 0 for the default, and is ignored by other modes.
+    pub fn from_u8(kind: u8, goal: u32) -> Option<Self> {
+        match (kind, goal) {
+            (0, _) => Some(GameMode::Marathon),
+            (1, 0) => Some(GameMode::sprint()),
+            (1, lines) if SPRINT_GOALS.contains(&lines) => Some(GameMode::Sprint { lines }),
+            (2, _) => Some(GameMode::ultra()),
+            (3 | 4, 0) => Some(GameMode::dig(DEFAULT_DIG_ROWS, kind == 4)),
+            (3 | 4, rows) if DIG_ROWS.contains(&rows) => Some(GameMode::dig(rows, kind == 4)),
+            (5, _) => Some(GameMode::Master),
+            _ => None,
+        }
+    }
+
+    /// Lines to clear to finish, if the mode has a line goal
+    pub fn line_goal(&self) -> Option<u32> {
+        match self {
+            GameMode::Sprint { lines } => Some(*lines),
+            _ => None,
+        }
+    }
+
+    /// Play time the game lasts, if the mode is timed
+    pub fn time_limit_ms(&self) -> Option<f64> {
+        match self {
+            GameMode::Ultra { time_ms } => Some(*time_ms as f64),
+            _ => None,
+        }
+    }
+
+    /// Garbage rows the game starts with
+    pub fn garbage_rows(&self) -> u32 {
+        match self {
+            GameMode::Dig { rows, .. } => *rows,
+            _ => 0,
+        }
+    }
+
+    /// Play time between rising garbage rows, if garbage rises
+    pub fn garbage_rise_ms(&self) -> Option<f64> {
+        match self {
+            GameMode::Dig {
+                rise_ms: Some(rise_ms),
+                ..
+            } => Some(*rise_ms as f64),
+            _ => None,
+        }
+    }
+
+    /// Whether `game` has reached the goal of the mode after `elapsed_ms` of play
+    pub fn is_complete(&self, game: &Game, elapsed_ms: f64) -> bool {
+        match self {
+            GameMode::Marathon => false,
+            GameMode::Sprint { lines } => game.lines_cleared >= *lines,
+            GameMode::Ultra { time_ms } => elapsed_ms >= *time_ms as f64,
+            GameMode::Dig { .. } => game.board.garbage_rows() == 0,
+            GameMode::Master => game.level >= MASTER_MAX_LEVEL,
+        }
+    }
+}
+
+/// ARE, line clear and lock delay of a Master section, in frames
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SectionDelays {
+    pub are_frames: u32,
+    pub line_clear_frames: u32,
+    pub lock_frames: u32,
+}
+
+/// Master section (0-9) that `level` is in
+pub fn master_section(level: u32) -> usize {
+    ((level / MASTER_SECTION_LEVELS) as usize).min(MASTER_DELAYS.len() - 1)
+}
+
+/// Delays for pieces in the Master section `level` is in
+pub fn master_delays(level: u32) -> SectionDelays {
+    let (are_frames, line_clear_frames, lock_frames) = MASTER_DELAYS[master_section(level)];
+    SectionDelays {
+        are_frames,
+        line_clear_frames,
+        lock_frames,
+    }
+}
+
+/// Master level after a lock that cleared `lines`. Each piece adds a level
+/// except at the last level of a section (x99 and 998), which only line
+/// clears get past.
+pub fn master_level(level: u32, lines: u32) -> u32 {
+    let level = if lines > 0 {
+        level + lines
+    } else if level % MASTER_SECTION_LEVELS == MASTER_SECTION_LEVELS - 1
+        || level == MASTER_MAX_LEVEL - 1
+    {
+        level
+    } else {
+        level + 1
+    };
+    level.min(MASTER_MAX_LEVEL)
+}
+
+/// TGM grade for a Master game: 9 to S9 by score, or GM for reaching level
+/// 999 with 126000 points in 13:30
+pub fn master_grade(score: u32, level: u32, elapsed_ms: f64) -> &'static str {
+    if level >= MASTER_MAX_LEVEL && score >= MASTER_GM_SCORE && elapsed_ms <= MASTER_GM_TIME_MS {
+        return "GM";
+    }
+    MASTER_GRADES
+        .iter()
+        .rev()
+        .find(|&&(_, needed)| score >= needed)
+        .map_or("9", |&(grade, _)| grade)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sprint_goals() {
+        assert_eq!(
+            GameMode::from_u8(1, 0),
+            Some(GameMode::Sprint { lines: 40 })
+        );
+        assert_eq!(
+            GameMode::from_u8(1, 100),
+            Some(GameMode::Sprint { lines: 100 })
+        );
+        assert_eq!(GameMode::from_u8(1, 35), None);
+        assert_eq!(GameMode::from_u8(0, 35), Some(GameMode::Marathon));
+        assert_eq!(GameMode::from_u8(2, 40), Some(GameMode::ultra()));
+        assert_eq!(GameMode::from_u8(9, 0), None);
+    }
+
+    #[test]
+    fn test_dig_modes() {
+        assert_eq!(
+            GameMode::from_u8(3, 0),
+            Some(GameMode::Dig {
+                rows: DEFAULT_DIG_ROWS,
+                rise_ms: None
+            })
+        );
+        let rising = GameMode::from_u8(4, 18).unwrap();
+        assert_eq!(rising.garbage_rows(), 18);
+        assert_eq!(rising.garbage_rise_ms(), Some(DIG_RISE_MS as f64));
+        assert_eq!(GameMode::from_u8(3, 7), None);
+    }
+
+    #[test]
+    fn test_master_level_stops_at_section_end() {
+        assert_eq!(master_level(0, 0), 1);
+        assert_eq!(master_level(99, 0), 99);
+        assert_eq!(master_level(99, 2), 101);
+        assert_eq!(master_level(998, 0), 998);
+        assert_eq!(master_level(997, 4), MASTER_MAX_LEVEL);
+        assert_eq!(master_section(MASTER_MAX_LEVEL), 9);
+        assert_eq!(master_delays(950).lock_frames, 17);
+    }
+
+    #[test]
+    fn test_master_grade() {
+        assert_eq!(master_grade(0, 0, 0.0), "9");
+        assert_eq!(master_grade(12000, 500, 0.0), "1");
+        assert_eq!(master_grade(130000, 998, 600_000.0), "S9");
+        assert_eq!(master_grade(130000, 999, 600_000.0), "GM");
+        assert_eq!(master_grade(130000, 999, 900_000.0), "S9");
+    }
+}
@@ -0,0 +1,353 @@
+use crate::piece::PieceType;
+use crate::rng::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Chooses the piece sequence. The game's `Rng` is passed in so every
+/// generator stays reproducible from the game seed.
+pub trait Randomizer {
+    fn next_piece(&mut self, rng: &mut Rng) -> PieceType;
+}
+
+/// Shuffled bag holding `copies` of every piece, refilled when empty
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawBag")]
+pub struct BagRandomizer {
+    copies: usize,
+    bag: Vec<PieceType>,
+}
+
+/// Deserialized form of `BagRandomizer`, checked before use
+#[derive(Deserialize)]
+struct RawBag {
+    copies: usize,
+    bag: Vec<PieceType>,
+}
+
+impl TryFrom<RawBag> for BagRandomizer {
+    type Error = &'static str;
+
+    fn try_from(raw: RawBag) -> Result<Self, Self::Error> {
+        if raw.copies == 0 {
+            return Err("bag must hold at least one copy of each piece");
+        }
+        Ok(Self {
+            copies: raw.copies,
+            bag: raw.bag,
+        })
+    }
+}
+
+impl BagRandomizer {
+    pub fn new(copies: usize) -> Self {
+        Self {
+            copies: copies.max(1),
+            bag: Vec::new(),
+        }
+    }
+}
+
+impl Randomizer for BagRandomizer {
+    fn next_piece(&mut self, rng: &mut Rng) -> PieceType {
+        if self.bag.is_empty() {
+            let mut pieces: Vec<PieceType> =
+                (0..self.copies).flat_map(|_| PieceType::all()).collect();
+            rng.shuffle(&mut pieces);
+            self.bag = pieces;
+        }
+        self.bag.pop().unwrap()
+    }
+}
+
+/// Every piece independently uniform
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PureRandomizer;
+
+impl Randomizer for PureRandomizer {
+    fn next_piece(&mut self, rng: &mut Rng) -> PieceType {
+        PieceType::all()[rng.below(7) as usize]
+    }
+}
+
+/// NES: roll an 8-sided die; on the blank side or a repeat of the last
+/// piece, reroll once from the seven pieces and keep that result
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NesRandomizer {
+    last: Option<PieceType>,
+}
+
+impl Randomizer for NesRandomizer {
+    fn next_piece(&mut self, rng: &mut Rng) -> PieceType {
+        let roll = rng.below(8) as usize;
+        let piece = match PieceType::all().get(roll) {
+            Some(&piece) if Some(piece) != self.last => piece,
+            _ => PieceType::all()[rng.below(7) as usize],
+        };
+        self.last = Some(piece);
+        piece
+    }
+}
+
+/// TGM: remembers the last four pieces and rerolls up to `rolls` times to
+/// avoid them. The first piece is never S, Z or O.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TgmRandomizer {
+    rolls: u32,
+    history: [PieceType; 4],
+    first: bool,
+}
+
+impl TgmRandomizer {
+    /// TGM1: 4 rolls, history starts as Z Z Z Z
+    pub fn tgm1() -> Self {
+        Self {
+            rolls: 4,
+            history: [PieceType::Z; 4],
+            first: true,
+        }
+    }
+
+    /// TGM2: 6 rolls, history starts as Z S S Z
+    pub fn tgm2() -> Self {
+        Self {
+            rolls: 6,
+            history: [PieceType::Z, PieceType::S, PieceType::S, PieceType::Z],
+            first: true,
+        }
+    }
+}
+
+impl Randomizer for TgmRandomizer {
+    fn next_piece(&mut self, rng: &mut Rng) -> PieceType {
+        let piece = if self.first {
+            self.first = false;
+            const FIRST: [PieceType; 4] = [PieceType::I, PieceType::J, PieceType::L, PieceType::T];
+            FIRST[rng.below(4) as usize]
+        } else {
+            let mut piece = PieceType::all()[rng.below(7) as usize];
+            for _ in 1..self.rolls {
+                if !self.history.contains(&piece) {
+                    break;
+                }
+                piece = PieceType::all()[rng.below(7) as usize];
+            }
+            piece
+        };
+        self.history.rotate_left(1);
+        self.history[3] = piece;
+        piece
+    }
+}
+
+/// Fixed sequence, repeated from the start when it runs out
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawScript")]
+pub struct ScriptedRandomizer {
+    sequence: Vec<PieceType>,
+    index: usize,
+}
+
+/// Deserialized form of `ScriptedRandomizer`, checked before use
+#[derive(Deserialize)]
+struct RawScript {
+    sequence: Vec<PieceType>,
+    index: usize,
+}
+
+impl TryFrom<RawScript> for ScriptedRandomizer {
+    type Error = &'static str;
+
+    fn try_from(raw: RawScript) -> Result<Self, Self::Error> {
+        if raw.index >= raw.sequence.len() {
+            return Err("script index must point into a non-empty sequence");
+        }
+        Ok(Self {
+            sequence: raw.sequence,
+            index: raw.index,
+        })
+    }
+}
+
+impl ScriptedRandomizer {
+    /// Returns `None` for an empty sequence
+    pub fn new(sequence: Vec<PieceType>) -> Option<Self> {
+        if sequence.is_empty() {
+            return None;
+        }
+        Some(Self { sequence, index: 0 })
+    }
+
+    /// Parse piece letters such as "TIOLJSZ" (case-insensitive, whitespace ignored)
+    pub fn parse(letters: &str) -> Option<Self> {
+        let sequence = letters
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| match c.to_ascii_uppercase() {
+                'I' => Some(PieceType::I),
+                'O' => Some(PieceType::O),
+                'T' => Some(PieceType::T),
+                'S' => Some(PieceType::S),
+                'Z' => Some(PieceType::Z),
+                'J' => Some(PieceType::J),
+                'L' => Some(PieceType::L),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Self::new(sequence)
+    }
+}
+
+impl Randomizer for ScriptedRandomizer {
+    fn next_piece(&mut self, _rng: &mut Rng) -> PieceType {
+        let piece = self.sequence[self.index];
+        self.index = (self.index + 1) % self.sequence.len();
+        piece
+    }
+}
+
+/// The built-in generators, chosen when a game is constructed.
+/// An enum rather than a boxed trait object so `Game` stays `Clone` and serializable.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PieceRandomizer {
+    Bag(BagRandomizer),
+    Pure(PureRandomizer),
+    Nes(NesRandomizer),
+    Tgm(TgmRandomizer),
+    Scripted(ScriptedRandomizer),
+}
+
+impl Default for PieceRandomizer {
+    fn default() -> Self {
+        Self::seven_bag()
+    }
+}
+
+impl PieceRandomizer {
+    pub fn seven_bag() -> Self {
+        PieceRandomizer::Bag(BagRandomizer::new(1))
+    }
+
+    pub fn fourteen_bag() -> Self {
+        PieceRandomizer::Bag(BagRandomizer::new(2))
+    }
+
+    /// Frontend selector: 0=7-bag, 1=14-bag, 2=pure random, 3=NES, 4=TGM1, 5=TGM2
+    pub fn from_u8(kind: u8) -> Option<Self> {
+        match kind {
+            0 => Some(Self::seven_bag()),
+            1 => Some(Self::fourteen_bag()),
+            2 => Some(PieceRandomizer::Pure(PureRandomizer)),
+            3 => Some(PieceRandomizer::Nes(NesRandomizer::default())),
+            4 => Some(PieceRandomizer::Tgm(TgmRandomizer::tgm1())),
+            5 => Some(PieceRandomizer::Tgm(TgmRandomizer::tgm2())),
+            _ => None,
+        }
+    }
+}
+
+impl Randomizer for PieceRandomizer {
+    fn next_piece(&mut self, rng: &mut Rng) -> PieceType {
+        match self {
+            PieceRandomizer::Bag(r) => r.next_piece(rng),
+            PieceRandomizer::Pure(r) => r.next_piece(rng),
+            PieceRandomizer::Nes(r) => r.next_piece(rng),
+            PieceRandomizer::Tgm(r) => r.next_piece(rng),
+            PieceRandomizer::Scripted(r) => r.next_piece(rng),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(randomizer: &mut impl Randomizer, count: usize) -> Vec<PieceType> {
+        let mut rng = Rng::new(42);
+        (0..count)
+            .map(|_| randomizer.next_piece(&mut rng))
+            .collect()
+    }
+
+    fn count(pieces: &[PieceType], piece: PieceType) -> usize {
+        pieces.iter().filter(|&&p| p == piece).count()
+    }
+
+    #[test]
+    fn test_fourteen_bag_deals_each_piece_twice() {
+        let pieces = draw(&mut PieceRandomizer::fourteen_bag(), 28);
+        for bag in pieces.chunks(14) {
+            for piece in PieceType::all() {
+                assert_eq!(count(bag, piece), 2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pure_random_covers_all_pieces() {
+        let pieces = draw(&mut PureRandomizer, 200);
+        for piece in PieceType::all() {
+            assert!(count(&pieces, piece) > 0);
+        }
+    }
+
+    #[test]
+    fn test_nes_repeats_less_than_pure_random() {
+        let repeats = |pieces: &[PieceType]| pieces.windows(2).filter(|w| w[0] == w[1]).count();
+        let nes = draw(&mut NesRandomizer::default(), 7000);
+        // Pure random repeats 1/7 of the time; NES only on a repeated reroll (~1/28)
+        assert!(repeats(&nes) < 7000 / 14);
+        assert!(repeats(&draw(&mut PureRandomizer, 7000)) > 7000 / 10);
+    }
+
+    #[test]
+    fn test_tgm_first_piece_and_history() {
+        for seed in 0..20 {
+            let mut rng = Rng::new(seed);
+            let mut tgm = TgmRandomizer::tgm2();
+            let first = tgm.next_piece(&mut rng);
+            assert!(![PieceType::S, PieceType::Z, PieceType::O].contains(&first));
+        }
+        let pieces = draw(&mut TgmRandomizer::tgm1(), 7000);
+        let repeats = pieces.windows(2).filter(|w| w[0] == w[1]).count();
+        assert!(repeats < 7000 / 20);
+    }
+
+    #[test]
+    fn test_scripted_sequence_loops() {
+        let mut scripted = ScriptedRandomizer::parse("t i o").unwrap();
+        let pieces = draw(&mut scripted, 5);
+        use PieceType::*;
+        assert_eq!(pieces, vec![T, I, O, T, I]);
+        assert!(ScriptedRandomizer::parse("").is_none());
+        assert!(ScriptedRandomizer::parse("TX").is_none());
+    }
+
+    #[test]
+    fn test_deserialize_checks_like_new() {
+        let bag = serde_json::to_string(&BagRandomizer::new(2)).unwrap();
+        assert_eq!(serde_json::from_str(&bag).ok(), Some(BagRandomizer::new(2)));
+        assert!(serde_json::from_str::<BagRandomizer>(r#"{"copies":0,"bag":[]}"#).is_err());
+
+        let scripted = ScriptedRandomizer::parse("TI").unwrap();
+        let json = serde_json::to_string(&scripted).unwrap();
+        assert_eq!(serde_json::from_str(&json).ok(), Some(scripted));
+        for json in [
+            r#"{"sequence":[],"index":0}"#,
+            r#"{"sequence":["T"],"index":1}"#,
+        ] {
+            assert!(serde_json::from_str::<ScriptedRandomizer>(json).is_err());
+        }
+    }
+
+    #[test]
+    fn test_selector() {
+        assert_eq!(
+            PieceRandomizer::from_u8(0),
+            Some(PieceRandomizer::seven_bag())
+        );
+        assert!(matches!(
+            PieceRandomizer::from_u8(3),
+            Some(PieceRandomizer::Nes(_))
+        ));
+        assert_eq!(PieceRandomizer::from_u8(6), None);
+    }
+}
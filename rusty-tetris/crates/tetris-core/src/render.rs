@@ -1,6 +1,6 @@
-use crate::board::{HEIGHT, WIDTH};
 use crate::game::Game;
-use crate::piece::Piece;
+use crate::rotation::{RotationKind, RotationSystem};
+use crate::stats::Statistics;
 use serde::{Deserialize, Serialize};
 
 /// A cell to render with position and color
@@ -21,10 +21,15 @@ pub struct RenderState {
     pub piece_cells: Vec<RenderCell>,
     /// Ghost piece cells
     pub ghost_cells: Vec<RenderCell>,
-    /// Next piece preview cells (relative coords, centered)
+    /// Next piece preview cells (relative coords, centered); the first queue slot
     pub next_cells: Vec<RenderCell>,
+    /// One preview per visible next queue slot, nearest first (same coords as `next_cells`)
+    pub queue_cells: Vec<Vec<RenderCell>>,
     /// Hold piece preview cells (relative coords, centered)
     pub hold_cells: Vec<RenderCell>,
+    /// Visible board size in cells
+    pub width: u8,
+    pub height: u8,
     /// Whether hold is available (affects opacity)
     pub hold_available: bool,
     /// Current score
@@ -33,27 +38,63 @@ pub struct RenderState {
     pub level: u32,
     /// Lines cleared
     pub lines: u32,
+    /// Classification of the most recent lock (`ClearKind` as u8)
+    pub last_clear: u8,
+    /// Consecutive line-clearing locks
+    pub combo: u32,
+    /// Whether a back-to-back chain is active
+    pub back_to_back: bool,
+    /// Rows (screen y) just cleared and still shown during the line-clear delay
+    pub clearing_rows: Vec<u8>,
+    /// Play time left in a timed mode (ms), frozen while paused; set by the controller
+    pub time_remaining_ms: Option<f64>,
+    /// Game totals; `time_ms` is set by the controller
+    pub stats: Statistics,
 }
 
 impl RenderState {
     pub fn from_game(game: &Game) -> Self {
-        let mut state = RenderState::default();
-
-        state.score = game.score;
-        state.level = game.level;
-        state.lines = game.lines_cleared;
-        state.hold_available = game.can_hold;
-
-        // Board cells (only non-empty)
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
-                if let Some(cell) = game.board.get(x as i32, y as i32) {
+        let width = game.board.width();
+        let height = game.board.height();
+        let mut state = RenderState {
+            width: width as u8,
+            height: height as u8,
+            score: game.score,
+            level: game.level,
+            lines: game.lines_cleared,
+            hold_available: game.can_hold,
+            last_clear: game.last_clear as u8,
+            combo: game.combo,
+            back_to_back: game.back_to_back,
+            stats: game.stats.clone(),
+            ..Default::default()
+        };
+
+        // During a line-clear delay, show the board as it was before the clear
+        let board = match &game.line_clear {
+            Some(clear) => {
+                state.clearing_rows = clear
+                    .rows
+                    .iter()
+                    .filter(|&&row| (row as usize) < height)
+                    .map(|&row| (height - 1 - row as usize) as u8)
+                    .collect();
+                &clear.board
+            }
+            None => &game.board,
+        };
+
+        // Board cells (only non-empty); cleared rows fade out
+        for y in 0..height {
+            let clearing = state.clearing_rows.contains(&((height - 1 - y) as u8));
+            for x in 0..width {
+                if let Some(cell) = board.get(x as i32, y as i32) {
                     if !cell.is_empty() {
                         state.board_cells.push(RenderCell {
                             x: x as u8,
-                            y: (HEIGHT - 1 - y) as u8, // Flip for SVG
+                            y: (height - 1 - y) as u8, // Flip for SVG
                             color: cell as u8,
-                            opacity: 255,
+                            opacity: if clearing { 128 } else { 255 },
                         });
                     }
                 }
@@ -64,10 +105,10 @@ impl RenderState {
         if let Some(piece) = &game.current_piece {
             let color = piece.piece_type.to_cell() as u8;
             for (px, py) in piece.get_blocks() {
-                if py >= 0 && py < HEIGHT as i32 {
+                if py >= 0 && py < height as i32 {
                     state.piece_cells.push(RenderCell {
                         x: px as u8,
-                        y: (HEIGHT as i32 - 1 - py) as u8,
+                        y: (height as i32 - 1 - py) as u8,
                         color,
                         opacity: 255,
                     });
@@ -79,10 +120,10 @@ impl RenderState {
                 let dy = piece.y - ghost_y;
                 for (px, py) in piece.get_blocks() {
                     let gy = py - dy;
-                    if gy >= 0 && gy < HEIGHT as i32 {
+                    if gy >= 0 && gy < height as i32 {
                         state.ghost_cells.push(RenderCell {
                             x: px as u8,
-                            y: (HEIGHT as i32 - 1 - gy) as u8,
+                            y: (height as i32 - 1 - gy) as u8,
                             color,
                             opacity: 77, // ~30%
                         });
@@ -91,21 +132,29 @@ impl RenderState {
             }
         }
 
-        // Next piece preview
-        state.next_cells = Self::preview_cells(game.next_piece, 255);
+        // Next queue previews
+        state.queue_cells = game
+            .peek_queue(game.preview_count())
+            .into_iter()
+            .map(|piece_type| Self::preview_cells(piece_type, game.rotation_system(), 255))
+            .collect();
+        state.next_cells = state.queue_cells.first().cloned().unwrap_or_default();
 
         // Hold piece preview
         if let Some(hold_type) = game.hold_piece {
             let opacity = if game.can_hold { 255 } else { 102 }; // 40% if unavailable
-            state.hold_cells = Self::preview_cells(hold_type, opacity);
+            state.hold_cells = Self::preview_cells(hold_type, game.rotation_system(), opacity);
         }
 
         state
     }
 
-    fn preview_cells(piece_type: crate::piece::PieceType, opacity: u8) -> Vec<RenderCell> {
-        let piece = Piece::new(piece_type);
-        let blocks = piece.get_blocks();
+    fn preview_cells(
+        piece_type: crate::piece::PieceType,
+        system: RotationKind,
+        opacity: u8,
+    ) -> Vec<RenderCell> {
+        let blocks = system.blocks(piece_type, system.spawn_rotation(piece_type));
         let color = piece_type.to_cell() as u8;
 
         // Find bounds for centering
@@ -138,11 +187,23 @@ impl RenderState {
             piece: self.cells_to_bytes(&self.piece_cells),
             ghost: self.cells_to_bytes(&self.ghost_cells),
             next: self.cells_to_bytes(&self.next_cells),
+            queue: self
+                .queue_cells
+                .iter()
+                .map(|cells| self.cells_to_bytes(cells))
+                .collect(),
             hold: self.cells_to_bytes(&self.hold_cells),
+            width: self.width,
+            height: self.height,
             score: self.score,
             level: self.level,
             lines: self.lines,
             hold_available: self.hold_available,
+            last_clear: self.last_clear,
+            combo: self.combo,
+            back_to_back: self.back_to_back,
+            clearing_rows: self.clearing_rows.clone(),
+            time_remaining_ms: self.time_remaining_ms,
         }
     }
 
@@ -160,9 +221,18 @@ pub struct RenderArrays {
     pub piece: Vec<u8>,
     pub ghost: Vec<u8>,
     pub next: Vec<u8>,
+    /// One flat cell array per preview slot
+    pub queue: Vec<Vec<u8>>,
     pub hold: Vec<u8>,
+    pub width: u8,
+    pub height: u8,
     pub score: u32,
     pub level: u32,
     pub lines: u32,
     pub hold_available: bool,
+    pub last_clear: u8,
+    pub combo: u32,
+    pub back_to_back: bool,
+    pub clearing_rows: Vec<u8>,
+    pub time_remaining_ms: Option<f64>,
 }
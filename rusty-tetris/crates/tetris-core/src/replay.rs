@@ -0,0 +1,382 @@
+use crate::board::{DEFAULT_HEIGHT, DEFAULT_WIDTH, MAX_HEIGHT};
+use crate::controller::{EntryDelay, GameController, GameState, LockDelay, FRAME_MS};
+use crate::game::{GameConfig, MAX_START_LEVEL};
+use crate::gravity::GravityKind;
+use crate::mode::GameMode;
+use crate::randomizer::PieceRandomizer;
+use serde::{Deserialize, Serialize};
+
+/// Replay format version written by `Replay::to_bytes`
+pub const REPLAY_VERSION: u8 = 1;
+/// First bytes of every encoded replay
+const MAGIC: &[u8; 4] = b"TRPL";
+/// Key byte flag for a press (clear for a release)
+const PRESSED: u8 = 0x80;
+/// Frames a replayed game may run past its last input before it counts as unfinished
+const MAX_IDLE_FRAMES: u64 = 10 * 60 * 60;
+/// Longest game a replay can hold: six hours of frames
+pub const MAX_REPLAY_FRAMES: u64 = 6 * 60 * 60 * 60;
+
+/// A key going down or up after `frame` frames of play
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct InputEvent {
+    pub frame: u64,
+    pub key: u8,
+    pub pressed: bool,
+}
+
+/// Controller settings the recorded game ran under
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    config: GameConfig,
+    lock_delay: LockDelay,
+    entry_delay: EntryDelay,
+}
+
+impl Settings {
+    /// Whether the controller can run these settings. Randomizers and gravity
+    /// tables are already checked when they are deserialized.
+    fn is_valid(&self) -> bool {
+        let delays = [
+            self.lock_delay.duration_ms,
+            self.entry_delay.are_ms,
+            self.entry_delay.line_clear_ms,
+        ];
+        self.config.seed.is_some()
+            && self.config.start_level <= MAX_START_LEVEL
+            && self.config.mode.garbage_rows() <= MAX_HEIGHT as u32
+            && delays.iter().all(|ms| ms.is_finite() && *ms >= 0.0)
+    }
+}
+
+/// How a game ended up, as a high score submission claims it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub score: u32,
+    pub level: u32,
+    pub lines: u32,
+    /// Scoring ruleset name ("guideline", "nes" or "tgm")
+    pub ruleset: String,
+}
+
+/// Everything needed to play a game again: its settings, with the seed it
+/// actually used, and every key event on the frame it happened
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    /// Game settings; `seed` is always set
+    pub config: GameConfig,
+    pub lock_delay: LockDelay,
+    pub entry_delay: EntryDelay,
+    /// Key events in the order they happened
+    pub inputs: Vec<InputEvent>,
+}
+
+impl Replay {
+    /// Encode as `TRPL`, the version byte, the settings as length-prefixed
+    /// JSON, then the event count and each event as a frame delta and a key
+    /// byte (high bit set for a press). Numbers are LEB128 varints.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let settings = serde_json::to_vec(&Settings {
+            config: self.config.clone(),
+            lock_delay: self.lock_delay,
+            entry_delay: self.entry_delay,
+        })
+        .unwrap_or_default();
+
+        let mut out = Vec::with_capacity(16 + settings.len() + self.inputs.len() * 2);
+        out.extend_from_slice(MAGIC);
+        out.push(REPLAY_VERSION);
+        write_varint(&mut out, settings.len() as u64);
+        out.extend_from_slice(&settings);
+        write_varint(&mut out, self.inputs.len() as u64);
+        let mut frame = 0;
+        for input in &self.inputs {
+            write_varint(&mut out, input.frame - frame);
+            out.push(input.key | if input.pressed { PRESSED } else { 0 });
+            frame = input.frame;
+        }
+        out
+    }
+
+    /// Decode `to_bytes` output; `None` if it isn't a replay of a known
+    /// version, its settings can't be played or it runs past `MAX_REPLAY_FRAMES`
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader { bytes, pos: 0 };
+        if reader.take(MAGIC.len())? != MAGIC || reader.take(1)?[0] != REPLAY_VERSION {
+            return None;
+        }
+        let settings_len = reader.varint()? as usize;
+        let settings: Settings = serde_json::from_slice(reader.take(settings_len)?).ok()?;
+        if !settings.is_valid() {
+            return None;
+        }
+
+        let count = reader.varint()?;
+        let mut inputs = Vec::new();
+        let mut frame = 0u64;
+        for _ in 0..count {
+            frame = frame.checked_add(reader.varint()?)?;
+            if frame > MAX_REPLAY_FRAMES {
+                return None;
+            }
+            let key = reader.take(1)?[0];
+            inputs.push(InputEvent {
+                frame,
+                key: key & !PRESSED,
+                pressed: key & PRESSED != 0,
+            });
+        }
+        if reader.pos != bytes.len() {
+            return None;
+        }
+
+        Some(Self {
+            config: settings.config,
+            lock_delay: settings.lock_delay,
+            entry_delay: settings.entry_delay,
+            inputs,
+        })
+    }
+
+    /// Whether the game ran under settings the leaderboards rank: Marathon on
+    /// the standard board with one of the built-in piece generators, gravity
+    /// curves and delay presets. Scripted pieces, other board sizes, gravity
+    /// tables and custom delays are for practice.
+    pub fn is_standard(&self) -> bool {
+        let config = &self.config;
+        config.mode == GameMode::Marathon
+            && (config.width, config.height) == (DEFAULT_WIDTH, DEFAULT_HEIGHT)
+            && (0..=5)
+                .any(|kind| PieceRandomizer::from_u8(kind).as_ref() == Some(&config.randomizer))
+            && matches!(config.gravity, GravityKind::Guideline | GravityKind::Nes)
+            && self.lock_delay == LockDelay::default()
+            && (0..=3).any(|kind| EntryDelay::from_u8(kind) == Some(self.entry_delay))
+    }
+
+    /// Play the game again headlessly and report how it ended. `None` if it
+    /// doesn't end in a game over or a finished goal within `MAX_IDLE_FRAMES`
+    /// of the last input (a game abandoned midway, or left paused) or within
+    /// `MAX_REPLAY_FRAMES`, or if it used undo.
+    pub fn result(&self) -> Option<GameResult> {
+        let mut controller = GameController::new();
+        controller.play_replay(self);
+        let last_frame = self.inputs.last().map_or(0, |input| input.frame);
+        let end_frame = last_frame
+            .saturating_add(MAX_IDLE_FRAMES)
+            .min(MAX_REPLAY_FRAMES);
+        while controller.state == GameState::Playing && controller.frames() <= end_frame {
+            controller.update(FRAME_MS);
+        }
+        if !matches!(controller.state, GameState::GameOver | GameState::Finished)
+            || controller.is_practice()
+        {
+            return None;
+        }
+        Some(GameResult {
+            score: controller.get_score(),
+            level: controller.get_level(),
+            lines: controller.get_lines(),
+            ruleset: controller.scoring().name().to_string(),
+        })
+    }
+
+    /// Whether playing the game again ends exactly as `claimed`
+    pub fn verify(&self, claimed: &GameResult) -> bool {
+        self.result().as_ref() == Some(claimed)
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let slice = self.bytes.get(self.pos..end)?;
+        self.pos = end;
+        Some(slice)
+    }
+
+    fn varint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::randomizer::ScriptedRandomizer;
+
+    fn sample() -> Replay {
+        Replay {
+            config: GameConfig {
+                seed: Some(42),
+                ..GameConfig::default()
+            },
+            lock_delay: LockDelay::default(),
+            entry_delay: EntryDelay::nes(),
+            inputs: vec![
+                InputEvent {
+                    frame: 3,
+                    key: 0,
+                    pressed: true,
+                },
+                InputEvent {
+                    frame: 300,
+                    key: 0,
+                    pressed: false,
+                },
+                InputEvent {
+                    frame: 300,
+                    key: 3,
+                    pressed: true,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let replay = sample();
+        let bytes = replay.to_bytes();
+        assert_eq!(&bytes[..5], b"TRPL\x01");
+        // Frame deltas 3, 297 (two bytes) and 0, each with a key byte
+        assert!(bytes.ends_with(&[3, 3, 0x80, 0xa9, 0x02, 0x00, 0x00, 0x83]));
+        assert_eq!(Replay::from_bytes(&bytes), Some(replay));
+    }
+
+    #[test]
+    fn test_result_of_a_recorded_game() {
+        let mut controller = GameController::new();
+        controller.start();
+        while controller.state == GameState::Playing {
+            controller.key_down(3);
+            controller.key_up(3);
+            controller.update(100.0);
+        }
+        let replay = controller.replay().unwrap().clone();
+        let result = replay.result().unwrap();
+        assert_eq!(result.score, controller.get_score());
+        assert_eq!(result.ruleset, "guideline");
+        assert!(replay.verify(&result));
+        assert!(!replay.verify(&GameResult {
+            score: result.score + 1,
+            ..result
+        }));
+
+        // A game left paused never ends
+        let mut paused = replay.clone();
+        paused.inputs.truncate(4);
+        paused.inputs.push(InputEvent {
+            frame: paused.inputs[3].frame,
+            key: 7,
+            pressed: true,
+        });
+        assert_eq!(paused.result(), None);
+    }
+
+    #[test]
+    fn test_rejects_unplayable_settings() {
+        let encode = |settings: &str| {
+            let mut bytes = b"TRPL\x01".to_vec();
+            write_varint(&mut bytes, settings.len() as u64);
+            bytes.extend_from_slice(settings.as_bytes());
+            bytes.push(0);
+            bytes
+        };
+        let settings = serde_json::to_string(&Settings {
+            config: sample().config,
+            lock_delay: LockDelay::default(),
+            entry_delay: EntryDelay::nes(),
+        })
+        .unwrap();
+        assert!(Replay::from_bytes(&encode(&settings)).is_some());
+        for (from, to) in [
+            ("\"seed\":42", "\"seed\":null"),
+            ("\"start_level\":1", "\"start_level\":4000000000"),
+            ("\"duration_ms\":500.0", "\"duration_ms\":-1.0"),
+            ("{\"Bag\":{\"copies\":1,", "{\"Bag\":{\"copies\":0,"),
+            ("\"gravity\":\"Nes\"", "\"gravity\":{\"Table\":[]}"),
+            (
+                "\"mode\":\"Marathon\"",
+                "\"mode\":{\"Dig\":{\"rows\":4000000000,\"rise_ms\":null}}",
+            ),
+        ] {
+            assert!(settings.contains(from), "{from}");
+            assert!(Replay::from_bytes(&encode(&settings.replace(from, to))).is_none());
+        }
+
+        // Inputs past the longest game
+        let mut replay = sample();
+        replay.inputs[2].frame = u64::MAX;
+        assert!(Replay::from_bytes(&replay.to_bytes()).is_none());
+        // Built directly, it still plays without overflowing
+        replay.result();
+    }
+
+    #[test]
+    fn test_standard_settings() {
+        let replay = sample();
+        assert!(replay.is_standard());
+        let variants = [
+            GameConfig {
+                randomizer: PieceRandomizer::Scripted(ScriptedRandomizer::parse("I").unwrap()),
+                ..replay.config.clone()
+            },
+            GameConfig {
+                width: 12,
+                ..replay.config.clone()
+            },
+            GameConfig {
+                gravity: GravityKind::table(&[1.0]).unwrap(),
+                ..replay.config.clone()
+            },
+            GameConfig {
+                mode: GameMode::sprint(),
+                ..replay.config.clone()
+            },
+        ];
+        for config in variants {
+            assert!(!Replay {
+                config,
+                ..replay.clone()
+            }
+            .is_standard());
+        }
+        assert!(!Replay {
+            entry_delay: EntryDelay {
+                are_ms: 1000.0,
+                line_clear_ms: 0.0,
+            },
+            ..replay
+        }
+        .is_standard());
+    }
+
+    #[test]
+    fn test_rejects_other_data() {
+        let mut bytes = sample().to_bytes();
+        assert!(Replay::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        bytes[4] = REPLAY_VERSION + 1;
+        assert!(Replay::from_bytes(&bytes).is_none());
+        assert!(Replay::from_bytes(b"not a replay").is_none());
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+/// Portable seedable PRNG (SplitMix64).
+/// Pure integer math, so a seed gives the same sequence on native and wasm builds.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Pick a fresh seed from the platform's entropy source
+    pub fn random_seed() -> u64 {
+        let mut bytes = [0u8; 8];
+        // Fall back to a fixed seed rather than failing if no entropy is available
+        let _ = getrandom::getrandom(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..bound` (rejection sampling, no modulo bias)
+    pub fn below(&mut self, bound: u32) -> u32 {
+        let bound = bound.max(1) as u64;
+        let zone = u64::MAX - (u64::MAX % bound);
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return (value % bound) as u32;
+            }
+        }
+    }
+
+    /// Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u32 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_sequence() {
+        // SplitMix64 reference values for seed 1234567
+        let mut rng = Rng::new(1234567);
+        assert_eq!(rng.next_u64(), 6457827717110365317);
+        assert_eq!(rng.next_u64(), 3203168211198807973);
+        assert_eq!(rng.next_u64(), 9817491932198370423);
+    }
+
+    #[test]
+    fn test_below_stays_in_range() {
+        let mut rng = Rng::new(7);
+        for bound in 1..50 {
+            assert!(rng.below(bound) < bound);
+        }
+    }
+}
@@ -0,0 +1,638 @@
+use crate::board::Board;
+use crate::piece::{Piece, PieceType, Rotation};
+use serde::{Deserialize, Serialize};
+
+/// Defines how pieces look in each rotation state and where they may kick.
+/// Offsets are relative to the piece origin, with y pointing up.
+pub trait RotationSystem {
+    /// Rotation state a piece spawns in
+    fn spawn_rotation(&self, _piece_type: PieceType) -> Rotation {
+        Rotation::R0
+    }
+
+    /// Block offsets of `piece_type` in `rotation`
+    fn blocks(&self, piece_type: PieceType, rotation: Rotation) -> [(i32, i32); 4];
+
+    /// Offsets to try, in order, when rotating `piece` to `to`; the first
+    /// one that fits is used. `(0, 0)` first means a plain rotation is tried.
+    fn kicks(&self, board: &Board, piece: &Piece, to: Rotation) -> &'static [(i32, i32)];
+}
+
+const NO_KICKS: &[(i32, i32)] = &[(0, 0)];
+
+/// Rotate offsets about the origin
+fn rotate_about_origin(blocks: [(i32, i32); 4], rotation: Rotation) -> [(i32, i32); 4] {
+    blocks.map(|(x, y)| match rotation {
+        Rotation::R0 => (x, y),
+        Rotation::R90 => (y, -x),
+        Rotation::R180 => (-x, -y),
+        Rotation::R270 => (-y, x),
+    })
+}
+
+/// Index into an 8-entry kick table ordered 0->R, R->0, R->2, 2->R, 2->L, L->2, L->0, 0->L
+fn kick_index(from: Rotation, to: Rotation) -> Option<usize> {
+    match (from, to) {
+        (Rotation::R0, Rotation::R90) => Some(0),
+        (Rotation::R90, Rotation::R0) => Some(1),
+        (Rotation::R90, Rotation::R180) => Some(2),
+        (Rotation::R180, Rotation::R90) => Some(3),
+        (Rotation::R180, Rotation::R270) => Some(4),
+        (Rotation::R270, Rotation::R180) => Some(5),
+        (Rotation::R270, Rotation::R0) => Some(6),
+        (Rotation::R0, Rotation::R270) => Some(7),
+        _ => None,
+    }
+}
+
+/// SRS wall kick data for J, L, S, T, Z pieces
+const SRS_KICKS_JLSTZ: [[(i32, i32); 5]; 8] = [
+    // 0 -> R (clockwise from 0)
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+    // R -> 0 (counter-clockwise from R)
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    // R -> 2 (clockwise from R)
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    // 2 -> R (counter-clockwise from 2)
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+    // 2 -> L (clockwise from 2)
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+    // L -> 2 (counter-clockwise from L)
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+    // L -> 0 (clockwise from L)
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+    // 0 -> L (counter-clockwise from 0)
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+];
+
+/// SRS wall kick data for I piece
+const SRS_KICKS_I: [[(i32, i32); 5]; 8] = [
+    // 0 -> R
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+    // R -> 0
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+    // R -> 2
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+    // 2 -> R
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+    // 2 -> L
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+    // L -> 2
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+    // L -> 0
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+    // 0 -> L
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+];
+
+/// SRS+ (TETR.IO) I kicks, mirrored so both directions behave alike
+const SRS_PLUS_KICKS_I: [[(i32, i32); 5]; 8] = [
+    // 0 -> R
+    [(0, 0), (1, 0), (-2, 0), (-2, -1), (1, 2)],
+    // R -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)],
+    // R -> 2
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+    // 2 -> R
+    [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)],
+    // 2 -> L
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+    // L -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+    // L -> 0
+    [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)],
+    // 0 -> L
+    [(0, 0), (-1, 0), (2, 0), (2, -1), (-1, 2)],
+];
+
+/// SRS+ (TETR.IO) 180 kicks, indexed by the starting state: 0->2, R->L, 2->0, L->R
+const SRS_PLUS_KICKS_180: [[(i32, i32); 6]; 4] = [
+    [(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)],
+    [(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],
+    [(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)],
+    [(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)],
+];
+
+/// Super Rotation System: guideline shapes rotating inside a 3x3 (or 4x4 for I) box
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Srs;
+
+impl Srs {
+    fn srs_blocks(piece_type: PieceType, rotation: Rotation) -> [(i32, i32); 4] {
+        match piece_type {
+            // The I box center sits between cells, half a cell right of and below the origin
+            PieceType::I => [(-1, 0), (0, 0), (1, 0), (2, 0)].map(|(x, y)| match rotation {
+                Rotation::R0 => (x, y),
+                Rotation::R90 => (y + 1, -x),
+                Rotation::R180 => (1 - x, -1 - y),
+                Rotation::R270 => (-y, x - 1),
+            }),
+            PieceType::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+            PieceType::T => rotate_about_origin([(-1, 0), (0, 0), (1, 0), (0, 1)], rotation),
+            PieceType::S => rotate_about_origin([(0, 0), (1, 0), (-1, 1), (0, 1)], rotation),
+            PieceType::Z => rotate_about_origin([(-1, 0), (0, 0), (0, 1), (1, 1)], rotation),
+            PieceType::J => rotate_about_origin([(-1, 1), (-1, 0), (0, 0), (1, 0)], rotation),
+            PieceType::L => rotate_about_origin([(-1, 0), (0, 0), (1, 0), (1, 1)], rotation),
+        }
+    }
+}
+
+impl RotationSystem for Srs {
+    fn blocks(&self, piece_type: PieceType, rotation: Rotation) -> [(i32, i32); 4] {
+        Self::srs_blocks(piece_type, rotation)
+    }
+
+    fn kicks(&self, _board: &Board, piece: &Piece, to: Rotation) -> &'static [(i32, i32)] {
+        if piece.piece_type == PieceType::O {
+            return NO_KICKS;
+        }
+        // The guideline has no 180 spin; borrow TETR.IO's table like most clients
+        if to == piece.rotation.half_turn() {
+            return &SRS_PLUS_KICKS_180[piece.rotation as usize];
+        }
+        let Some(index) = kick_index(piece.rotation, to) else {
+            return NO_KICKS;
+        };
+        match piece.piece_type {
+            PieceType::I => &SRS_KICKS_I[index],
+            _ => &SRS_KICKS_JLSTZ[index],
+        }
+    }
+}
+
+/// SRS with TETR.IO's symmetric I kicks
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SrsPlus;
+
+impl RotationSystem for SrsPlus {
+    fn blocks(&self, piece_type: PieceType, rotation: Rotation) -> [(i32, i32); 4] {
+        Srs::srs_blocks(piece_type, rotation)
+    }
+
+    fn kicks(&self, board: &Board, piece: &Piece, to: Rotation) -> &'static [(i32, i32)] {
+        match (piece.piece_type, kick_index(piece.rotation, to)) {
+            (PieceType::I, Some(index)) => &SRS_PLUS_KICKS_I[index],
+            _ => Srs.kicks(board, piece, to),
+        }
+    }
+}
+
+/// Shapes shared by ARS and the Sega system: flat side down at spawn,
+/// and every orientation rests on the bottom of its 3x3 box
+fn bottom_aligned_blocks(piece_type: PieceType, rotation: Rotation) -> [(i32, i32); 4] {
+    let vertical = matches!(rotation, Rotation::R90 | Rotation::R270);
+    match (piece_type, rotation) {
+        (PieceType::I, _) if vertical => [(1, 1), (1, 0), (1, -1), (1, -2)],
+        (PieceType::I, _) => [(-1, 0), (0, 0), (1, 0), (2, 0)],
+        (PieceType::O, _) => [(0, 0), (1, 0), (0, -1), (1, -1)],
+        (PieceType::S, _) if vertical => [(-1, 1), (-1, 0), (0, 0), (0, -1)],
+        (PieceType::S, _) => [(0, 0), (1, 0), (-1, -1), (0, -1)],
+        (PieceType::Z, _) if vertical => [(1, 1), (0, 0), (1, 0), (0, -1)],
+        (PieceType::Z, _) => [(-1, 0), (0, 0), (0, -1), (1, -1)],
+        (PieceType::T, Rotation::R0) => [(-1, 0), (0, 0), (1, 0), (0, -1)],
+        (PieceType::T, Rotation::R90) => [(0, 1), (0, 0), (0, -1), (-1, 0)],
+        (PieceType::T, Rotation::R180) => [(-1, -1), (0, -1), (1, -1), (0, 0)],
+        (PieceType::T, Rotation::R270) => [(0, 1), (0, 0), (0, -1), (1, 0)],
+        (PieceType::J, Rotation::R0) => [(-1, 0), (0, 0), (1, 0), (1, -1)],
+        (PieceType::J, Rotation::R90) => [(0, 1), (0, 0), (-1, -1), (0, -1)],
+        (PieceType::J, Rotation::R180) => [(-1, 0), (-1, -1), (0, -1), (1, -1)],
+        (PieceType::J, Rotation::R270) => [(0, 1), (1, 1), (0, 0), (0, -1)],
+        (PieceType::L, Rotation::R0) => [(-1, 0), (0, 0), (1, 0), (-1, -1)],
+        (PieceType::L, Rotation::R90) => [(-1, 1), (0, 1), (0, 0), (0, -1)],
+        (PieceType::L, Rotation::R180) => [(1, 0), (-1, -1), (0, -1), (1, -1)],
+        (PieceType::L, Rotation::R270) => [(0, 1), (0, 0), (0, -1), (1, -1)],
+    }
+}
+
+/// Arika Rotation System (TGM): bottom-aligned shapes, kicks one cell right
+/// then left. I never kicks, and J, L and T don't kick when the first blocked
+/// cell, reading left to right and top to bottom, is in the center column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ars;
+
+impl RotationSystem for Ars {
+    fn blocks(&self, piece_type: PieceType, rotation: Rotation) -> [(i32, i32); 4] {
+        bottom_aligned_blocks(piece_type, rotation)
+    }
+
+    fn kicks(&self, board: &Board, piece: &Piece, to: Rotation) -> &'static [(i32, i32)] {
+        const ARS_KICKS: &[(i32, i32)] = &[(0, 0), (1, 0), (-1, 0)];
+        match piece.piece_type {
+            PieceType::I | PieceType::O => NO_KICKS,
+            PieceType::S | PieceType::Z => ARS_KICKS,
+            PieceType::T | PieceType::J | PieceType::L => {
+                let mut blocked: Vec<(i32, i32)> = self
+                    .blocks(piece.piece_type, to)
+                    .into_iter()
+                    .filter(|&(dx, dy)| !board.is_valid_position(piece.x + dx, piece.y + dy))
+                    .collect();
+                blocked.sort_by_key(|&(dx, dy)| (-dy, dx));
+                match blocked.first() {
+                    Some(&(0, _)) => NO_KICKS,
+                    _ => ARS_KICKS,
+                }
+            }
+        }
+    }
+}
+
+/// Nintendo Rotation System (NES): no kicks. T, J and L turn about their
+/// center; I, S and Z have two states, standing in the right-hand column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Nes;
+
+impl RotationSystem for Nes {
+    fn blocks(&self, piece_type: PieceType, rotation: Rotation) -> [(i32, i32); 4] {
+        let vertical = matches!(rotation, Rotation::R90 | Rotation::R270);
+        match piece_type {
+            PieceType::I if vertical => [(0, 2), (0, 1), (0, 0), (0, -1)],
+            PieceType::I => [(-2, 0), (-1, 0), (0, 0), (1, 0)],
+            PieceType::O => [(-1, 0), (0, 0), (-1, -1), (0, -1)],
+            PieceType::S if vertical => [(0, 1), (0, 0), (1, 0), (1, -1)],
+            PieceType::S => [(0, 0), (1, 0), (-1, -1), (0, -1)],
+            PieceType::Z if vertical => [(1, 1), (0, 0), (1, 0), (0, -1)],
+            PieceType::Z => [(-1, 0), (0, 0), (0, -1), (1, -1)],
+            PieceType::T => rotate_about_origin([(-1, 0), (0, 0), (1, 0), (0, -1)], rotation),
+            PieceType::J => rotate_about_origin([(-1, 0), (0, 0), (1, 0), (1, -1)], rotation),
+            PieceType::L => rotate_about_origin([(-1, 0), (0, 0), (1, 0), (-1, -1)], rotation),
+        }
+    }
+
+    fn kicks(&self, _board: &Board, _piece: &Piece, _to: Rotation) -> &'static [(i32, i32)] {
+        NO_KICKS
+    }
+}
+
+/// Sega (1988) style: the bottom-aligned ARS shapes without kicks, with the
+/// vertical I, S and Z all standing in the right-hand column
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sega;
+
+impl RotationSystem for Sega {
+    fn blocks(&self, piece_type: PieceType, rotation: Rotation) -> [(i32, i32); 4] {
+        match (piece_type, rotation) {
+            (PieceType::S, Rotation::R90 | Rotation::R270) => [(0, 1), (0, 0), (1, 0), (1, -1)],
+            _ => bottom_aligned_blocks(piece_type, rotation),
+        }
+    }
+
+    fn kicks(&self, _board: &Board, _piece: &Piece, _to: Rotation) -> &'static [(i32, i32)] {
+        NO_KICKS
+    }
+}
+
+/// The built-in rotation systems, chosen when a game is constructed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RotationKind {
+    #[default]
+    Srs,
+    SrsPlus,
+    Ars,
+    Nes,
+    Sega,
+}
+
+impl RotationKind {
+    /// Frontend selector: 0=SRS, 1=SRS+, 2=ARS, 3=NES, 4=Sega
+    pub fn from_u8(kind: u8) -> Option<Self> {
+        match kind {
+            0 => Some(RotationKind::Srs),
+            1 => Some(RotationKind::SrsPlus),
+            2 => Some(RotationKind::Ars),
+            3 => Some(RotationKind::Nes),
+            4 => Some(RotationKind::Sega),
+            _ => None,
+        }
+    }
+
+    fn system(self) -> &'static dyn RotationSystem {
+        match self {
+            RotationKind::Srs => &Srs,
+            RotationKind::SrsPlus => &SrsPlus,
+            RotationKind::Ars => &Ars,
+            RotationKind::Nes => &Nes,
+            RotationKind::Sega => &Sega,
+        }
+    }
+}
+
+impl RotationSystem for RotationKind {
+    fn spawn_rotation(&self, piece_type: PieceType) -> Rotation {
+        self.system().spawn_rotation(piece_type)
+    }
+
+    fn blocks(&self, piece_type: PieceType, rotation: Rotation) -> [(i32, i32); 4] {
+        self.system().blocks(piece_type, rotation)
+    }
+
+    fn kicks(&self, board: &Board, piece: &Piece, to: Rotation) -> &'static [(i32, i32)] {
+        self.system().kicks(board, piece, to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Cell;
+    use crate::game::{Game, GameConfig, MoveResult};
+    use crate::piece::Turn;
+
+    fn game_with(rotation: RotationKind) -> Game {
+        Game::with_config(GameConfig {
+            seed: Some(1),
+            rotation,
+            ..GameConfig::default()
+        })
+    }
+
+    fn place(game: &mut Game, piece_type: PieceType, x: i32, y: i32, rotation: Rotation) {
+        let mut piece = Piece::spawn(piece_type, &game.board, game.rotation_system());
+        piece.x = x;
+        piece.y = y;
+        piece.rotation = rotation;
+        game.current_piece = Some(piece);
+    }
+
+    fn sorted(mut blocks: [(i32, i32); 4]) -> [(i32, i32); 4] {
+        blocks.sort();
+        blocks
+    }
+
+    /// Occupy every cell except those the target rotation would use after `kick`,
+    /// so that exactly this test of the table succeeds
+    fn only_allow(game: &mut Game, kick: (i32, i32), to: Rotation) {
+        let piece = game.current_piece.clone().unwrap();
+        let system = piece.system;
+        let free: Vec<(i32, i32)> = system
+            .blocks(piece.piece_type, to)
+            .iter()
+            .map(|&(dx, dy)| (piece.x + dx + kick.0, piece.y + dy + kick.1))
+            .collect();
+        for y in 0..game.board.height() as i32 {
+            for x in 0..game.board.width() as i32 {
+                if !free.contains(&(x, y)) {
+                    game.board.set(x, y, Cell::O);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_srs_states_match_guideline() {
+        use PieceType::*;
+        // Published SRS states for I, read from the 4x4 box with the origin at row 1, column 1
+        let i = |r| sorted(Srs.blocks(I, r));
+        assert_eq!(i(Rotation::R0), [(-1, 0), (0, 0), (1, 0), (2, 0)]);
+        assert_eq!(i(Rotation::R90), [(1, -2), (1, -1), (1, 0), (1, 1)]);
+        assert_eq!(i(Rotation::R180), [(-1, -1), (0, -1), (1, -1), (2, -1)]);
+        assert_eq!(i(Rotation::R270), [(0, -2), (0, -1), (0, 0), (0, 1)]);
+        // T points up at spawn and right after a clockwise turn
+        assert_eq!(
+            sorted(Srs.blocks(T, Rotation::R90)),
+            [(0, -1), (0, 0), (0, 1), (1, 0)]
+        );
+        assert_eq!(Srs.blocks(O, Rotation::R0), Srs.blocks(O, Rotation::R270));
+    }
+
+    #[test]
+    fn test_srs_kicks_follow_published_tables() {
+        // Each (from, to, kick list) from the guideline JLSTZ and I tables
+        let jlstz = [
+            (
+                Rotation::R0,
+                Rotation::R90,
+                [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+            ),
+            (
+                Rotation::R90,
+                Rotation::R180,
+                [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+            ),
+            (
+                Rotation::R180,
+                Rotation::R270,
+                [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+            ),
+            (
+                Rotation::R270,
+                Rotation::R0,
+                [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+            ),
+        ];
+        for (from, to, table) in jlstz {
+            for (test, &kick) in table.iter().enumerate() {
+                let mut game = game_with(RotationKind::Srs);
+                place(&mut game, PieceType::T, 4, 10, from);
+                only_allow(&mut game, kick, to);
+                let turn = if to == from.clockwise() {
+                    Turn::Clockwise
+                } else {
+                    Turn::CounterClockwise
+                };
+                assert_eq!(
+                    game.rotate(turn),
+                    MoveResult::Success,
+                    "{from:?} test {test}"
+                );
+                let piece = game.current_piece.as_ref().unwrap();
+                assert_eq!(
+                    (piece.x, piece.y, piece.rotation),
+                    (4 + kick.0, 10 + kick.1, to)
+                );
+            }
+        }
+
+        let i_table = [
+            (
+                Rotation::R0,
+                Rotation::R90,
+                [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+            ),
+            (
+                Rotation::R90,
+                Rotation::R0,
+                [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+            ),
+            (
+                Rotation::R0,
+                Rotation::R270,
+                [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+            ),
+        ];
+        for (from, to, table) in i_table {
+            for &kick in &table {
+                let mut game = game_with(RotationKind::Srs);
+                place(&mut game, PieceType::I, 4, 10, from);
+                only_allow(&mut game, kick, to);
+                let turn = if to == from.clockwise() {
+                    Turn::Clockwise
+                } else {
+                    Turn::CounterClockwise
+                };
+                assert_eq!(game.rotate(turn), MoveResult::Success);
+                let piece = game.current_piece.as_ref().unwrap();
+                assert_eq!((piece.x, piece.y), (4 + kick.0, 10 + kick.1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_180_kicks_follow_tetrio_table() {
+        let tables = [
+            (
+                Rotation::R0,
+                [(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)],
+            ),
+            (
+                Rotation::R90,
+                [(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],
+            ),
+            (
+                Rotation::R180,
+                [(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)],
+            ),
+            (
+                Rotation::R270,
+                [(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)],
+            ),
+        ];
+        for rotation in [RotationKind::Srs, RotationKind::SrsPlus] {
+            for (from, table) in tables {
+                for &kick in &table {
+                    let mut game = game_with(rotation);
+                    place(&mut game, PieceType::T, 4, 10, from);
+                    only_allow(&mut game, kick, from.half_turn());
+                    assert_eq!(game.rotate(Turn::Half), MoveResult::Success);
+                    let piece = game.current_piece.as_ref().unwrap();
+                    assert_eq!(
+                        (piece.x, piece.y, piece.rotation),
+                        (4 + kick.0, 10 + kick.1, from.half_turn())
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_180_on_the_floor_kicks_up() {
+        let mut game = game_with(RotationKind::SrsPlus);
+        place(&mut game, PieceType::T, 4, 0, Rotation::R0);
+        assert_eq!(game.rotate(Turn::Half), MoveResult::Success);
+        let piece = game.current_piece.as_ref().unwrap();
+        assert_eq!((piece.y, piece.rotation), (1, Rotation::R180));
+
+        // Systems without 180 kicks only turn in place
+        let mut game = game_with(RotationKind::Nes);
+        place(&mut game, PieceType::T, 4, 1, Rotation::R0);
+        assert_eq!(game.rotate(Turn::Half), MoveResult::Success);
+        place(&mut game, PieceType::T, 4, 0, Rotation::R180);
+        assert_eq!(game.rotate(Turn::Half), MoveResult::Failed);
+    }
+
+    #[test]
+    fn test_srs_plus_i_kicks_are_symmetric() {
+        // SRS+ prefers the one-cell kick toward the side the I turns to
+        let mut game = game_with(RotationKind::SrsPlus);
+        place(&mut game, PieceType::I, 4, 10, Rotation::R0);
+        only_allow(&mut game, (1, 0), Rotation::R90);
+        assert_eq!(game.rotate(Turn::Clockwise), MoveResult::Success);
+        assert_eq!(game.current_piece.as_ref().unwrap().x, 5);
+
+        // From spawn the first kick is one cell toward the turn direction
+        let piece = Piece::spawn(PieceType::I, &game.board, RotationKind::SrsPlus);
+        let board = Board::new();
+        assert_eq!(SrsPlus.kicks(&board, &piece, Rotation::R90)[1], (1, 0));
+        assert_eq!(SrsPlus.kicks(&board, &piece, Rotation::R270)[1], (-1, 0));
+        // Other pieces use the SRS tables
+        let t = Piece::spawn(PieceType::T, &board, RotationKind::SrsPlus);
+        assert_eq!(
+            SrsPlus.kicks(&board, &t, Rotation::R90),
+            Srs.kicks(&board, &t, Rotation::R90)
+        );
+    }
+
+    #[test]
+    fn test_ars_kicks_right_then_left() {
+        let mut game = game_with(RotationKind::Ars);
+        // Against the right wall the vertical L must kick left to lie flat
+        place(&mut game, PieceType::L, 9, 5, Rotation::R90);
+        assert_eq!(game.rotate(Turn::Clockwise), MoveResult::Success);
+        let piece = game.current_piece.as_ref().unwrap();
+        assert_eq!((piece.x, piece.rotation), (8, Rotation::R180));
+
+        // I never kicks
+        let mut game = game_with(RotationKind::Ars);
+        place(&mut game, PieceType::I, 4, 10, Rotation::R90);
+        game.board.set(3, 10, Cell::O);
+        assert_eq!(game.rotate(Turn::Clockwise), MoveResult::Failed);
+    }
+
+    #[test]
+    fn test_ars_center_column_rule() {
+        // T pointing down: its stem cell above the center is the first blocked one
+        let mut game = game_with(RotationKind::Ars);
+        place(&mut game, PieceType::T, 4, 10, Rotation::R0);
+        game.board.set(4, 11, Cell::O);
+        assert_eq!(game.rotate(Turn::Clockwise), MoveResult::Failed);
+
+        // Blocked first in the left column instead: kicks right
+        let mut game = game_with(RotationKind::Ars);
+        place(&mut game, PieceType::T, 4, 10, Rotation::R0);
+        game.board.set(3, 10, Cell::O);
+        assert_eq!(game.rotate(Turn::Clockwise), MoveResult::Success);
+        assert_eq!(game.current_piece.as_ref().unwrap().x, 5);
+    }
+
+    #[test]
+    fn test_nes_and_sega_never_kick() {
+        // T against the left wall, stem pointing right: turning either way needs a kick
+        for (rotation, state, expected) in [
+            (RotationKind::Nes, Rotation::R270, MoveResult::Failed),
+            (RotationKind::Sega, Rotation::R270, MoveResult::Failed),
+            (RotationKind::Srs, Rotation::R90, MoveResult::Success),
+        ] {
+            let mut game = game_with(rotation);
+            place(&mut game, PieceType::T, 0, 5, state);
+            assert_eq!(game.rotate(Turn::Clockwise), expected, "{rotation:?}");
+            place(&mut game, PieceType::T, 0, 5, state);
+            assert_eq!(
+                game.rotate(Turn::CounterClockwise),
+                expected,
+                "{rotation:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_right_handed_vertical_pieces() {
+        use PieceType::*;
+        let columns = |system: &dyn RotationSystem, piece, rotation| {
+            let mut columns: Vec<i32> =
+                system.blocks(piece, rotation).iter().map(|b| b.0).collect();
+            columns.sort();
+            columns.dedup();
+            columns
+        };
+        for system in [&Sega as &dyn RotationSystem, &Nes] {
+            for piece in [S, Z] {
+                assert_eq!(
+                    columns(system, piece, Rotation::R90),
+                    vec![0, 1],
+                    "{piece:?}"
+                );
+            }
+            // The vertical I stands right of the horizontal one's middle
+            let flat = columns(system, I, Rotation::R0);
+            assert!(2 * columns(system, I, Rotation::R90)[0] > flat[0] + flat[3]);
+        }
+        // ARS keeps its vertical S on the left
+        assert_eq!(columns(&Ars, S, Rotation::R90), vec![-1, 0]);
+    }
+
+    #[test]
+    fn test_selector() {
+        assert_eq!(RotationKind::from_u8(0), Some(RotationKind::Srs));
+        assert_eq!(RotationKind::from_u8(4), Some(RotationKind::Sega));
+        assert_eq!(RotationKind::from_u8(5), None);
+    }
+}
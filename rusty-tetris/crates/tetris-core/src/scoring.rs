@@ -0,0 +1,363 @@
+use serde::{Deserialize, Serialize};
+
+/// Guideline combo bonus per combo step, multiplied by level
+const COMBO_BONUS: u32 = 50;
+
+/// T-spin result of a piece lock (3-corner rule)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum SpinKind {
+    #[default]
+    None,
+    Mini,
+    Full,
+}
+
+/// Classification of a piece lock by lines cleared and T-spin
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum ClearKind {
+    #[default]
+    None = 0,
+    Single = 1,
+    Double = 2,
+    Triple = 3,
+    Tetris = 4,
+    TSpinMini = 5,
+    TSpinMiniSingle = 6,
+    TSpinMiniDouble = 7,
+    TSpin = 8,
+    TSpinSingle = 9,
+    TSpinDouble = 10,
+    TSpinTriple = 11,
+}
+
+impl ClearKind {
+    pub fn classify(lines: u32, spin: SpinKind) -> Self {
+        match (spin, lines) {
+            (SpinKind::None, 0) => ClearKind::None,
+            (SpinKind::None, 1) => ClearKind::Single,
+            (SpinKind::None, 2) => ClearKind::Double,
+            (SpinKind::None, 3) => ClearKind::Triple,
+            (SpinKind::None, _) => ClearKind::Tetris,
+            (SpinKind::Mini, 0) => ClearKind::TSpinMini,
+            (SpinKind::Mini, 1) => ClearKind::TSpinMiniSingle,
+            // A mini can't clear more than two rows; anything bigger counts as full
+            (SpinKind::Mini, 2) => ClearKind::TSpinMiniDouble,
+            (_, 0) => ClearKind::TSpin,
+            (_, 1) => ClearKind::TSpinSingle,
+            (_, 2) => ClearKind::TSpinDouble,
+            (_, _) => ClearKind::TSpinTriple,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => ClearKind::Single,
+            2 => ClearKind::Double,
+            3 => ClearKind::Triple,
+            4 => ClearKind::Tetris,
+            5 => ClearKind::TSpinMini,
+            6 => ClearKind::TSpinMiniSingle,
+            7 => ClearKind::TSpinMiniDouble,
+            8 => ClearKind::TSpin,
+            9 => ClearKind::TSpinSingle,
+            10 => ClearKind::TSpinDouble,
+            11 => ClearKind::TSpinTriple,
+            _ => ClearKind::None,
+        }
+    }
+
+    /// Number of rows cleared
+    pub fn lines(self) -> u32 {
+        match self {
+            ClearKind::None | ClearKind::TSpinMini | ClearKind::TSpin => 0,
+            ClearKind::Single | ClearKind::TSpinMiniSingle | ClearKind::TSpinSingle => 1,
+            ClearKind::Double | ClearKind::TSpinMiniDouble | ClearKind::TSpinDouble => 2,
+            ClearKind::Triple | ClearKind::TSpinTriple => 3,
+            ClearKind::Tetris => 4,
+        }
+    }
+
+    pub fn is_t_spin(self) -> bool {
+        self as u8 >= ClearKind::TSpinMini as u8
+    }
+
+    /// Tetrises and line-clearing T-spins keep a back-to-back chain going
+    pub fn is_difficult(self) -> bool {
+        self.lines() > 0 && (self == ClearKind::Tetris || self.is_t_spin())
+    }
+
+    /// Guideline base points, multiplied by level
+    pub fn base_score(self) -> u32 {
+        match self {
+            ClearKind::None => 0,
+            ClearKind::Single => 100,
+            ClearKind::Double => 300,
+            ClearKind::Triple => 500,
+            ClearKind::Tetris => 800,
+            ClearKind::TSpinMini => 100,
+            ClearKind::TSpinMiniSingle => 200,
+            ClearKind::TSpinMiniDouble => 400,
+            ClearKind::TSpin => 400,
+            ClearKind::TSpinSingle => 800,
+            ClearKind::TSpinDouble => 1200,
+            ClearKind::TSpinTriple => 1600,
+        }
+    }
+
+    /// Display name for frontends
+    pub fn name(self) -> &'static str {
+        match self {
+            ClearKind::None => "",
+            ClearKind::Single => "SINGLE",
+            ClearKind::Double => "DOUBLE",
+            ClearKind::Triple => "TRIPLE",
+            ClearKind::Tetris => "TETRIS",
+            ClearKind::TSpinMini => "T-SPIN MINI",
+            ClearKind::TSpinMiniSingle => "T-SPIN MINI SINGLE",
+            ClearKind::TSpinMiniDouble => "T-SPIN MINI DOUBLE",
+            ClearKind::TSpin => "T-SPIN",
+            ClearKind::TSpinSingle => "T-SPIN SINGLE",
+            ClearKind::TSpinDouble => "T-SPIN DOUBLE",
+            ClearKind::TSpinTriple => "T-SPIN TRIPLE",
+        }
+    }
+}
+
+/// What a scoring ruleset sees of one piece lock
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LockScore {
+    pub clear: ClearKind,
+    /// Level before the lock
+    pub level: u32,
+    /// Line-clearing locks in a row before this one
+    pub combo: u32,
+    /// Lines cleared by those locks
+    pub combo_lines: u32,
+    /// Whether the previous clear was a Tetris or T-spin
+    pub back_to_back: bool,
+    /// Whether the lock emptied the board
+    pub perfect_clear: bool,
+    /// Rows the piece was soft dropped
+    pub soft_drop_rows: u32,
+    /// Rows the piece fell on its hard drop
+    pub hard_drop_rows: u32,
+}
+
+/// Point values of a ruleset
+pub trait ScoringRules {
+    /// Points for one soft-dropped row, awarded as it drops
+    fn soft_drop(&self, _level: u32) -> u32 {
+        0
+    }
+
+    /// Points for a hard drop of `rows`, awarded before the lock
+    fn hard_drop(&self, _rows: u32) -> u32 {
+        0
+    }
+
+    /// Points for a lock, with any line clear, combo, back-to-back or perfect clear bonus
+    fn lock(&self, lock: &LockScore) -> u32;
+}
+
+/// Modern guideline scoring: T-spins, back-to-back, combos and perfect clears
+pub struct Guideline;
+
+impl ScoringRules for Guideline {
+    fn soft_drop(&self, _level: u32) -> u32 {
+        1
+    }
+
+    fn hard_drop(&self, rows: u32) -> u32 {
+        rows * 2
+    }
+
+    fn lock(&self, lock: &LockScore) -> u32 {
+        let clear = lock.clear;
+        let mut points = clear.base_score() * lock.level;
+        if clear.is_difficult() && lock.back_to_back {
+            points = points * 3 / 2;
+        }
+        if clear.lines() > 0 {
+            // `combo` counts the previous clears, i.e. the combo step of this one
+            points += COMBO_BONUS * lock.combo * lock.level;
+        }
+        if lock.perfect_clear {
+            let back_to_back = clear == ClearKind::Tetris && lock.back_to_back;
+            points += perfect_clear_bonus(clear.lines(), back_to_back) * lock.level;
+        }
+        points
+    }
+}
+
+/// NES scoring: 40/100/300/1200 × (NES level + 1), where NES level 0 is level 1 here.
+/// Soft drops earn a point per row; no other bonuses.
+pub struct Nes;
+
+impl ScoringRules for Nes {
+    fn soft_drop(&self, _level: u32) -> u32 {
+        1
+    }
+
+    fn lock(&self, lock: &LockScore) -> u32 {
+        let base = match lock.clear.lines() {
+            0 => 0,
+            1 => 40,
+            2 => 100,
+            3 => 300,
+            _ => 1200,
+        };
+        base * lock.level
+    }
+}
+
+/// TGM scoring: (⌈(level + lines) / 4⌉ + drop rows) × lines × combo × bravo.
+/// The combo factor grows by 2 × lines - 2 per clear in a chain; bravo is 4
+/// for a perfect clear.
+pub struct Tgm;
+
+impl ScoringRules for Tgm {
+    fn lock(&self, lock: &LockScore) -> u32 {
+        let lines = lock.clear.lines();
+        if lines == 0 {
+            return 0;
+        }
+        let combo = 1 + 2 * (lock.combo_lines + lines) - 2 * (lock.combo + 1);
+        let bravo = if lock.perfect_clear { 4 } else { 1 };
+        let drop = lock.soft_drop_rows + lock.hard_drop_rows;
+        ((lock.level + lines).div_ceil(4) + drop) * lines * combo * bravo
+    }
+}
+
+/// The built-in scoring rulesets, chosen when a game is constructed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScoringKind {
+    #[default]
+    Guideline,
+    Nes,
+    Tgm,
+}
+
+impl ScoringKind {
+    /// Frontend selector: 0=Guideline, 1=NES, 2=TGM
+    pub fn from_u8(kind: u8) -> Option<Self> {
+        match kind {
+            0 => Some(ScoringKind::Guideline),
+            1 => Some(ScoringKind::Nes),
+            2 => Some(ScoringKind::Tgm),
+            _ => None,
+        }
+    }
+
+    /// Tag stored with high scores
+    pub fn name(self) -> &'static str {
+        match self {
+            ScoringKind::Guideline => "guideline",
+            ScoringKind::Nes => "nes",
+            ScoringKind::Tgm => "tgm",
+        }
+    }
+
+    fn rules(self) -> &'static dyn ScoringRules {
+        match self {
+            ScoringKind::Guideline => &Guideline,
+            ScoringKind::Nes => &Nes,
+            ScoringKind::Tgm => &Tgm,
+        }
+    }
+}
+
+impl ScoringRules for ScoringKind {
+    fn soft_drop(&self, level: u32) -> u32 {
+        self.rules().soft_drop(level)
+    }
+
+    fn hard_drop(&self, rows: u32) -> u32 {
+        self.rules().hard_drop(rows)
+    }
+
+    fn lock(&self, lock: &LockScore) -> u32 {
+        self.rules().lock(lock)
+    }
+}
+
+/// Guideline perfect clear bonus by lines cleared, multiplied by level
+pub fn perfect_clear_bonus(lines: u32, back_to_back: bool) -> u32 {
+    match lines {
+        1 => 800,
+        2 => 1200,
+        3 => 1800,
+        4 if back_to_back => 3200,
+        4 => 2000,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(ClearKind::classify(0, SpinKind::None), ClearKind::None);
+        assert_eq!(ClearKind::classify(4, SpinKind::None), ClearKind::Tetris);
+        assert_eq!(ClearKind::classify(0, SpinKind::Mini), ClearKind::TSpinMini);
+        assert_eq!(ClearKind::classify(2, SpinKind::Full), ClearKind::TSpinDouble);
+        assert_eq!(ClearKind::classify(3, SpinKind::Mini), ClearKind::TSpinTriple);
+    }
+
+    #[test]
+    fn test_u8_round_trip() {
+        for value in 0..12 {
+            assert_eq!(ClearKind::from_u8(value) as u8, value);
+        }
+    }
+
+    fn clear(kind: ClearKind, level: u32) -> LockScore {
+        LockScore {
+            clear: kind,
+            level,
+            ..LockScore::default()
+        }
+    }
+
+    #[test]
+    fn test_nes_points() {
+        assert_eq!(Nes.lock(&clear(ClearKind::Single, 1)), 40);
+        assert_eq!(Nes.lock(&clear(ClearKind::Tetris, 1)), 1200);
+        assert_eq!(Nes.lock(&clear(ClearKind::Double, 10)), 1000);
+        // T-spins score by lines only
+        assert_eq!(Nes.lock(&clear(ClearKind::TSpinTriple, 2)), 600);
+        assert_eq!(Nes.hard_drop(20), 0);
+    }
+
+    #[test]
+    fn test_tgm_points() {
+        // (⌈(3 + 4) / 4⌉ + 0) × 4 × 7
+        assert_eq!(Tgm.lock(&clear(ClearKind::Tetris, 3)), 56);
+
+        // A single after a double in the same chain: combo factor 3 + 0
+        let chained = LockScore {
+            combo: 1,
+            combo_lines: 2,
+            soft_drop_rows: 2,
+            ..clear(ClearKind::Single, 1)
+        };
+        assert_eq!(Tgm.lock(&chained), (1 + 2) * 3);
+
+        let bravo = LockScore {
+            perfect_clear: true,
+            ..clear(ClearKind::Single, 1)
+        };
+        assert_eq!(Tgm.lock(&bravo), 4);
+        assert_eq!(Tgm.lock(&clear(ClearKind::TSpin, 5)), 0);
+    }
+
+    #[test]
+    fn test_difficult_clears() {
+        assert!(ClearKind::Tetris.is_difficult());
+        assert!(ClearKind::TSpinMiniSingle.is_difficult());
+        assert!(!ClearKind::Triple.is_difficult());
+        assert!(!ClearKind::TSpin.is_difficult());
+    }
+}
@@ -0,0 +1,167 @@
+use crate::controller::{GameController, GameState};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Snapshot format version written by `GameController::save_snapshot`. Bump it
+/// whenever the controller's serialized fields change, except to add a field
+/// marked `#[serde(default)]`, which snapshots without it still load with.
+pub const SNAPSHOT_VERSION: u64 = 2;
+
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    version: u64,
+    controller: &'a GameController,
+}
+
+impl GameController {
+    /// Save the controller (game, timers, held keys, settings and the replay
+    /// recorded so far) as versioned JSON. The undo and redo history is left
+    /// out, so a resumed game can only undo pieces placed after it resumed.
+    pub fn save_snapshot(&self) -> String {
+        serde_json::to_string(&Snapshot {
+            version: SNAPSHOT_VERSION,
+            controller: self,
+        })
+        .unwrap_or_default()
+    }
+
+    /// Restore a controller from `save_snapshot` output. A game in progress
+    /// comes back paused. `None` for anything that isn't a consistent snapshot
+    /// of this version: older snapshots are rejected until the format changes
+    /// in a way worth migrating them here.
+    pub fn load_snapshot(json: &str) -> Option<Self> {
+        let mut snapshot: Value = serde_json::from_str(json).ok()?;
+        if snapshot.get("version")?.as_u64()? != SNAPSHOT_VERSION {
+            return None;
+        }
+        let mut controller: Self =
+            serde_json::from_value(snapshot.get_mut("controller")?.take()).ok()?;
+        if !controller.is_consistent() {
+            return None;
+        }
+        if controller.state == GameState::Playing {
+            // As the pause key, so the recording sees it
+            controller.key_down(7);
+        }
+        Some(controller)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KEY_REDO, KEY_UNDO};
+
+    fn play(controller: &mut GameController, steps: u32) {
+        for step in 0..steps {
+            match step % 12 {
+                0 => controller.key_down(step as u8 / 12 % 2),
+                5 => controller.key_up(step as u8 / 12 % 2),
+                8 => controller.key_down(3),
+                9 => controller.key_up(3),
+                _ => {}
+            }
+            controller.update(20.0);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_resumes_the_same_game() {
+        let mut controller = GameController::new();
+        controller.start_with_seed(5);
+        play(&mut controller, 50);
+        controller.key_down(0);
+        controller.update(7.0);
+
+        let mut loaded = GameController::load_snapshot(&controller.save_snapshot()).unwrap();
+        assert_eq!(loaded.state, GameState::Paused);
+        controller.key_down(7);
+        for controller in [&mut controller, &mut loaded] {
+            controller.key_down(7);
+            play(controller, 100);
+        }
+
+        let game = controller.game.as_ref().unwrap();
+        let loaded_game = loaded.game.as_ref().unwrap();
+        assert_eq!(controller.frames(), loaded.frames());
+        assert_eq!(game.score, loaded_game.score);
+        assert_eq!(
+            game.board.get_visible_grid(),
+            loaded_game.board.get_visible_grid()
+        );
+        assert_eq!(controller.replay(), loaded.replay());
+    }
+
+    #[test]
+    fn test_leaves_out_undo_history() {
+        let mut controller = GameController::new();
+        controller.start_with_seed(5);
+        for _ in 0..4 {
+            controller.key_down(3);
+            controller.key_up(3);
+        }
+        controller.key_down(KEY_UNDO);
+        controller.key_up(KEY_UNDO);
+        let inputs = controller.replay().unwrap().inputs.len();
+
+        let snapshot = controller.save_snapshot();
+        assert!(!snapshot.contains("undo_history") && !snapshot.contains("redo_history"));
+        let mut loaded = GameController::load_snapshot(&snapshot).unwrap();
+        assert!(loaded.is_practice());
+        let pieces = loaded.game.as_ref().unwrap().pieces;
+        loaded.key_down(7);
+        // Nothing to undo or redo, and nothing recorded for the replay
+        loaded.key_down(KEY_UNDO);
+        loaded.key_down(KEY_REDO);
+        assert_eq!(loaded.game.as_ref().unwrap().pieces, pieces);
+        assert_eq!(loaded.replay().unwrap().inputs.len(), inputs + 2);
+    }
+
+    #[test]
+    fn test_rejects_inconsistent_games() {
+        let mut controller = GameController::new();
+        controller.start_with_seed(3);
+        play(&mut controller, 60);
+        let snapshot: Value = serde_json::from_str(&controller.save_snapshot()).unwrap();
+        assert!(snapshot["controller"]["game"]["current_piece"].is_object());
+        let load = |edit: fn(&mut Value)| {
+            let mut snapshot = snapshot.clone();
+            edit(&mut snapshot["controller"]["game"]);
+            GameController::load_snapshot(&snapshot.to_string())
+        };
+        assert!(load(|_| {}).is_some());
+
+        // A grid that doesn't match the board size
+        assert!(load(|game| {
+            game["board"]["grid"].as_array_mut().unwrap().pop();
+        })
+        .is_none());
+        assert!(load(|game| game["board"]["width"] = 12.into()).is_none());
+        assert!(load(|game| {
+            for row in game["board"]["grid"].as_array_mut().unwrap() {
+                row.as_array_mut().unwrap().push("Empty".into());
+            }
+            game["board"]["width"] = 11.into();
+        })
+        .is_some());
+        assert!(load(|game| game["board"]["width"] = 0.into()).is_none());
+
+        // A short queue, or a falling piece outside the board
+        assert!(load(|game| {
+            game["queue"].as_array_mut().unwrap().pop();
+        })
+        .is_none());
+        assert!(load(|game| game["current_piece"]["x"] = (-5).into()).is_none());
+    }
+
+    #[test]
+    fn test_rejects_other_versions() {
+        let snapshot = GameController::new().save_snapshot();
+        assert!(GameController::load_snapshot(&snapshot).is_some());
+        let version = format!(r#""version":{SNAPSHOT_VERSION}"#);
+        let older = snapshot.replacen(&version, r#""version":1"#, 1);
+        assert!(GameController::load_snapshot(&older).is_none());
+        assert!(GameController::load_snapshot(&format!("{{{version}}}")).is_none());
+        assert!(GameController::load_snapshot("not json").is_none());
+    }
+}
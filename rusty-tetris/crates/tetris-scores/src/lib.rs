@@ -2,17 +2,25 @@ use serde::{Deserialize, Serialize};
 use std::ffi::{CStr, CString};
 use std::fs;
 use std::os::raw::c_char;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 const MAX_SCORES: usize = 100;
 const TOP_SCORES: usize = 10;
 
+fn default_ruleset() -> String {
+    "guideline".to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Score {
     pub name: String,
     pub score: u32,
     pub level: u32,
     pub lines: u32,
+    /// Scoring ruleset the game was played under; older entries count as guideline
+    #[serde(default = "default_ruleset")]
+    pub ruleset: String,
     pub timestamp: u64,
 }
 
@@ -43,7 +51,7 @@ impl ScoreStore {
 
     fn add_score(&mut self, score: Score) {
         self.scores.push(score);
-        self.scores.sort_by(|a, b| b.score.cmp(&a.score));
+        self.scores.sort_by_key(|s| std::cmp::Reverse(s.score));
         self.scores.truncate(MAX_SCORES);
     }
 
@@ -54,11 +62,16 @@ impl ScoreStore {
 
 // FFI exports for Deno
 
-static mut DATA_PATH: Option<String> = None;
+static DATA_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+fn data_path() -> Option<PathBuf> {
+    DATA_PATH.lock().ok()?.clone()
+}
 
 /// Initialize the score store with a data file path
 /// Returns 0 on success, -1 on failure
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn scores_init(path: *const c_char) -> i32 {
     if path.is_null() {
         return -1;
@@ -71,8 +84,9 @@ pub extern "C" fn scores_init(path: *const c_char) -> i32 {
         }
     };
 
-    unsafe {
-        DATA_PATH = Some(path_str);
+    match DATA_PATH.lock() {
+        Ok(mut data_path) => *data_path = Some(PathBuf::from(path_str)),
+        Err(_) => return -1,
     }
 
     0
@@ -82,12 +96,12 @@ pub extern "C" fn scores_init(path: *const c_char) -> i32 {
 /// Caller must free the returned string with scores_free_string
 #[no_mangle]
 pub extern "C" fn scores_get_top() -> *mut c_char {
-    let path = match unsafe { &DATA_PATH } {
-        Some(p) => Path::new(p),
+    let path = match data_path() {
+        Some(p) => p,
         None => return std::ptr::null_mut(),
     };
 
-    let store = ScoreStore::load(path);
+    let store = ScoreStore::load(&path);
     let top = store.get_top();
 
     match serde_json::to_string(&top) {
@@ -100,16 +114,18 @@ pub extern "C" fn scores_get_top() -> *mut c_char {
 }
 
 /// Add a new score
-/// score_json should be a JSON object with name, score, level, lines fields
+/// score_json should be a JSON object with name, score, level, lines and
+/// optionally ruleset fields
 /// Returns 0 on success, -1 on failure
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn scores_add(score_json: *const c_char) -> i32 {
     if score_json.is_null() {
         return -1;
     }
 
-    let path = match unsafe { &DATA_PATH } {
-        Some(p) => Path::new(p),
+    let path = match data_path() {
+        Some(p) => p,
         None => return -1,
     };
 
@@ -126,6 +142,8 @@ pub extern "C" fn scores_add(score_json: *const c_char) -> i32 {
         score: u32,
         level: u32,
         lines: u32,
+        #[serde(default = "default_ruleset")]
+        ruleset: String,
     }
 
     let input: ScoreInput = match serde_json::from_str(json_str) {
@@ -138,16 +156,17 @@ pub extern "C" fn scores_add(score_json: *const c_char) -> i32 {
         score: input.score,
         level: input.level,
         lines: input.lines,
+        ruleset: input.ruleset.chars().take(20).collect(),
         timestamp: std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_millis() as u64)
             .unwrap_or(0),
     };
 
-    let mut store = ScoreStore::load(path);
+    let mut store = ScoreStore::load(&path);
     store.add_score(score);
 
-    match store.save(path) {
+    match store.save(&path) {
         Ok(_) => 0,
         Err(_) => -1,
     }
@@ -155,6 +174,7 @@ pub extern "C" fn scores_add(score_json: *const c_char) -> i32 {
 
 /// Free a string returned by this library
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn scores_free_string(ptr: *mut c_char) {
     if !ptr.is_null() {
         unsafe {
@@ -167,12 +187,12 @@ pub extern "C" fn scores_free_string(ptr: *mut c_char) {
 /// Returns 1 if qualifies, 0 if not, -1 on error
 #[no_mangle]
 pub extern "C" fn scores_qualifies(score: u32) -> i32 {
-    let path = match unsafe { &DATA_PATH } {
-        Some(p) => Path::new(p),
+    let path = match data_path() {
+        Some(p) => p,
         None => return -1,
     };
 
-    let store = ScoreStore::load(path);
+    let store = ScoreStore::load(&path);
 
     if store.scores.len() < TOP_SCORES {
         return 1;
@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::catch_unwind;
+use tetris_core::replay::{GameResult, Replay};
+
+fn default_ruleset() -> String {
+    "guideline".to_string()
+}
+
+/// Result claimed by a score submission; other fields (like the name) are ignored
+#[derive(Debug, Clone, Deserialize)]
+pub struct Claim {
+    pub score: u32,
+    pub level: u32,
+    pub lines: u32,
+    /// Submissions without a ruleset count as guideline
+    #[serde(default = "default_ruleset")]
+    pub ruleset: String,
+}
+
+impl From<Claim> for GameResult {
+    fn from(claim: Claim) -> Self {
+        Self {
+            score: claim.score,
+            level: claim.level,
+            lines: claim.lines,
+            ruleset: claim.ruleset,
+        }
+    }
+}
+
+/// Outcome of checking a replay against a claim
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    /// Whether the replayed game ends exactly as claimed
+    pub matches: bool,
+    /// How the replayed game really ended; `None` if the replay can't be read,
+    /// wasn't played under standard settings or its game never ends
+    pub result: Option<GameResult>,
+}
+
+/// Re-simulate the game in `replay` (bytes from `export_replay`) and get how
+/// it ended. `None` if the replay can't be read, wasn't played under the
+/// standard settings (`Replay::is_standard`) or its game never ends.
+pub fn standard_result(replay: &[u8]) -> Option<GameResult> {
+    Replay::from_bytes(replay)
+        .filter(Replay::is_standard)
+        .and_then(|replay| replay.result())
+}
+
+/// Compare how the game in `replay` ends with `claim`. Only games played under
+/// the standard settings can match.
+pub fn verify(replay: &[u8], claim: Claim) -> Report {
+    let result = standard_result(replay);
+    Report {
+        matches: result == Some(claim.into()),
+        result,
+    }
+}
+
+// FFI exports for Deno
+
+/// Check a replay against a score submission
+/// claim_json should be a JSON object with score, level, lines and optionally
+/// ruleset fields
+/// Returns 1 if the replay ends as claimed, 0 if not, -1 on failure
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn verify_replay(replay: *const u8, len: usize, claim_json: *const c_char) -> i32 {
+    // A panic must not unwind into the host, which would abort it
+    catch_unwind(|| verify_replay_inner(replay, len, claim_json)).unwrap_or(-1)
+}
+
+fn verify_replay_inner(replay: *const u8, len: usize, claim_json: *const c_char) -> i32 {
+    if replay.is_null() || claim_json.is_null() {
+        return -1;
+    }
+
+    let replay = unsafe { std::slice::from_raw_parts(replay, len) };
+    let json_str = unsafe {
+        match CStr::from_ptr(claim_json).to_str() {
+            Ok(s) => s,
+            Err(_) => return -1,
+        }
+    };
+
+    let claim: Claim = match serde_json::from_str(json_str) {
+        Ok(c) => c,
+        Err(_) => return -1,
+    };
+
+    if verify(replay, claim).matches {
+        1
+    } else {
+        0
+    }
+}
+
+/// Re-simulate a replay and get how its game ended as JSON (score, level,
+/// lines and ruleset)
+/// Returns null if the replay can't be read, isn't standard or its game never ends
+/// Caller must free the returned string with verify_free_string
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn verify_replay_result(replay: *const u8, len: usize) -> *mut c_char {
+    catch_unwind(|| verify_replay_result_inner(replay, len)).unwrap_or(std::ptr::null_mut())
+}
+
+fn verify_replay_result_inner(replay: *const u8, len: usize) -> *mut c_char {
+    if replay.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let replay = unsafe { std::slice::from_raw_parts(replay, len) };
+    let result = match standard_result(replay) {
+        Some(r) => r,
+        None => return std::ptr::null_mut(),
+    };
+
+    match serde_json::to_string(&result) {
+        Ok(json) => match CString::new(json) {
+            Ok(cstr) => cstr.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Free a string returned by this library
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn verify_free_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        unsafe {
+            drop(CString::from_raw(ptr));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tetris_core::controller::{GameController, GameState};
+    use tetris_core::randomizer::{PieceRandomizer, ScriptedRandomizer};
+
+    #[test]
+    fn test_verify_recorded_game() {
+        let mut controller = GameController::new();
+        controller.start();
+        while controller.state == GameState::Playing {
+            controller.key_down(3);
+            controller.key_up(3);
+            controller.update(100.0);
+        }
+        let replay = controller.replay().unwrap().to_bytes();
+        let claim = |score: u32| {
+            serde_json::from_str::<Claim>(&format!(
+                r#"{{"name": "ann", "score": {score}, "level": {}, "lines": {}}}"#,
+                controller.get_level(),
+                controller.get_lines()
+            ))
+            .unwrap()
+        };
+
+        let report = verify(&replay, claim(controller.get_score()));
+        assert!(report.matches);
+        assert_eq!(report.result.unwrap().ruleset, "guideline");
+        assert!(!verify(&replay, claim(controller.get_score() + 100)).matches);
+        assert_eq!(verify(&replay[1..], claim(0)).result, None);
+    }
+
+    #[test]
+    fn test_rejects_non_standard_settings() {
+        let mut controller = GameController::new();
+        controller.config.randomizer =
+            PieceRandomizer::Scripted(ScriptedRandomizer::parse("I").unwrap());
+        controller.start();
+        while controller.state == GameState::Playing {
+            controller.key_down(3);
+            controller.key_up(3);
+            controller.update(100.0);
+        }
+        let replay = controller.replay().unwrap();
+        let claim = Claim {
+            score: controller.get_score(),
+            level: controller.get_level(),
+            lines: controller.get_lines(),
+            ruleset: default_ruleset(),
+        };
+        assert!(replay.result().is_some());
+        assert_eq!(
+            verify(&replay.to_bytes(), claim),
+            Report {
+                matches: false,
+                result: None,
+            }
+        );
+        let bytes = replay.to_bytes();
+        assert!(verify_replay_result(bytes.as_ptr(), bytes.len()).is_null());
+
+        // Through the FFI, an input frame past the longest game is a mismatch rather than a crash
+        let mut crafted = replay.clone();
+        crafted.config.randomizer = PieceRandomizer::default();
+        crafted.inputs.last_mut().unwrap().frame = u64::MAX;
+        let bytes = crafted.to_bytes();
+        let claim_json = CString::new(r#"{"score": 0, "level": 1, "lines": 0}"#).unwrap();
+        assert_eq!(
+            verify_replay(bytes.as_ptr(), bytes.len(), claim_json.as_ptr()),
+            0
+        );
+        assert!(verify_replay_result(bytes.as_ptr(), bytes.len()).is_null());
+    }
+}
@@ -0,0 +1,56 @@
+//! Check a replay against a claimed result:
+//!
+//!     tetris-verify <replay-file> '{"score": 1200, "level": 2, "lines": 14}'
+//!
+//! Prints a JSON report and exits with 0 if the replay ends as claimed, 1 if
+//! it doesn't and 2 on bad arguments. Without a claim it prints how the
+//! replayed game ended (exit code 1 if it isn't standard or never ends).
+
+use std::process::ExitCode;
+use tetris_verify::{standard_result, verify, Claim};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (path, claim) = match args.as_slice() {
+        [path] => (path, None),
+        [path, claim] => (path, Some(claim)),
+        _ => {
+            eprintln!("usage: tetris-verify <replay-file> [claim-json]");
+            return ExitCode::from(2);
+        }
+    };
+
+    let replay = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("cannot read {path}: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let Some(claim) = claim else {
+        let result = standard_result(&replay);
+        println!("{}", serde_json::to_string(&result).unwrap_or_default());
+        return if result.is_some() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+    };
+
+    let claim: Claim = match serde_json::from_str(claim) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("invalid claim: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let report = verify(&replay, claim);
+    println!("{}", serde_json::to_string(&report).unwrap_or_default());
+    if report.matches {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -1,10 +1,20 @@
-import init, { Tetris, get_color } from "../../pkg/tetris_core.js";
+import init, { Tetris, get_clear_name, get_color } from "../../pkg/tetris_core.js";
 import { getHighScores, submitScore, type Score } from "./api.ts";
 
 // Constants
 const CELL_SIZE = 30;
-const BOARD_WIDTH = 10;
-const BOARD_HEIGHT = 20;
+const MAX_BOARD_PIXELS = 600; // Cells shrink so tall boards still fit
+const PREVIEW_SLOT_ROWS = 2.5; // 2-row piece plus a half-row gap
+const DEFAULT_PREVIEWS = 5;
+const MAX_PREVIEWS = 6;
+
+// Next queue length, optionally set with ?previews=<0-6>
+const previewCount = readPreviewCount();
+
+// Rust key codes past KEY_RESTART
+const KEY_ROTATE_180 = 10;
+const KEY_UNDO = 11;
+const KEY_REDO = 12;
 
 // Key mappings: keyboard code -> Rust key code
 const KEY_MAP: Record<string, number> = {
@@ -15,6 +25,7 @@ const KEY_MAP: Record<string, number> = {
   ArrowUp: 4,
   KeyX: 4,
   KeyZ: 5,
+  KeyA: KEY_ROTATE_180,
   ControlLeft: 5,
   ControlRight: 5,
   KeyC: 6,
@@ -24,6 +35,8 @@ const KEY_MAP: Record<string, number> = {
   Escape: 7,
   Enter: 8,
   KeyR: 9,
+  KeyU: KEY_UNDO,
+  KeyY: KEY_REDO,
 };
 
 // Game states
@@ -31,6 +44,10 @@ const STATE_IDLE = 0;
 const STATE_PLAYING = 1;
 const STATE_PAUSED = 2;
 const STATE_GAME_OVER = 3;
+const STATE_FINISHED = 4;
+
+// localStorage key of the game saved on leaving the page
+const SNAPSHOT_KEY = "rusty-tetris-snapshot";
 
 // DOM elements
 let svg: SVGSVGElement;
@@ -43,16 +60,233 @@ let holdSvg: SVGSVGElement;
 let scoreEl: HTMLElement;
 let levelEl: HTMLElement;
 let linesEl: HTMLElement;
+let timeEl: HTMLElement;
+let comboEl: HTMLElement;
+let clearLabelEl: HTMLElement;
+let b2bLabelEl: HTMLElement;
 let scoresEl: HTMLElement;
+let statsEl: HTMLElement;
+
+// Board size, optionally set with ?width=<4-20>&height=<4-40> and read back after clamping
+let boardWidth = 10;
+let boardHeight = 20;
+let cellSize = CELL_SIZE;
 
 // Game state
 let tetris: Tetris;
 let lastTime = 0;
 let lastState = STATE_IDLE;
 
+const RANDOMIZERS: Record<string, number> = { bag7: 0, bag14: 1, random: 2, nes: 3, tgm1: 4, tgm2: 5 };
+
+// Optional ?randomizer=<name> or ?pieces=<letters> URL parameters for training setups
+function applyRandomizerParams(
+  setRandomizer: (kind: number) => boolean,
+  setPieceSequence: (letters: string) => boolean,
+) {
+  const params = new URLSearchParams(window.location.search);
+  const pieces = params.get("pieces");
+  const name = params.get("randomizer");
+  if (pieces !== null && !setPieceSequence(pieces)) {
+    console.warn(`Ignoring invalid piece sequence: ${pieces}`);
+  } else if (pieces === null && name !== null && !(name in RANDOMIZERS && setRandomizer(RANDOMIZERS[name]))) {
+    console.warn(`Unknown randomizer: ${name}`);
+  }
+}
+
+const ROTATION_SYSTEMS: Record<string, number> = { srs: 0, srsplus: 1, ars: 2, nes: 3, sega: 4 };
+
+// Optional ?rotation=<name> URL parameter
+function applyRotationParam(setRotationSystem: (kind: number) => boolean) {
+  const name = new URLSearchParams(window.location.search).get("rotation");
+  if (name !== null && !(name in ROTATION_SYSTEMS && setRotationSystem(ROTATION_SYSTEMS[name]))) {
+    console.warn(`Unknown rotation system: ${name}`);
+  }
+}
+
+const GRAVITY_CURVES: Record<string, number> = { guideline: 0, nes: 1 };
+
+// Optional ?gravity=<name> or ?gravity=<G per level, comma-separated> and ?level=<start level> URL parameters
+function applyGravityParams(
+  setGravity: (kind: number) => boolean,
+  setGravityTable: (gravity: Float64Array) => boolean,
+  setStartLevel: (level: number) => void,
+) {
+  const params = new URLSearchParams(window.location.search);
+  const name = params.get("gravity");
+  if (name !== null) {
+    const ok = name in GRAVITY_CURVES
+      ? setGravity(GRAVITY_CURVES[name])
+      : setGravityTable(new Float64Array(name.split(",").map(Number)));
+    if (!ok) {
+      console.warn(`Unknown gravity curve: ${name}`);
+    }
+  }
+  const level = parseInt(params.get("level") ?? "", 10);
+  if (!Number.isNaN(level)) {
+    setStartLevel(level);
+  }
+}
+
+const MODES: Record<string, number> = { marathon: 0, sprint: 1, ultra: 2, dig: 3, "dig-rising": 4, master: 5 };
+
+// Optional ?mode=<name> URL parameter, with ?lines=20|40|100 for Sprint or 5|10|18 garbage rows for Dig
+function applyModeParam(setMode: (kind: number, goal: number) => boolean) {
+  const params = new URLSearchParams(window.location.search);
+  const name = params.get("mode");
+  const goal = parseInt(params.get("lines") ?? "0", 10);
+  if (name !== null && !(name in MODES && setMode(MODES[name], Number.isNaN(goal) ? 0 : goal))) {
+    console.warn(`Unknown mode: ${name}`);
+  }
+}
+
+// m:ss.mmm
+function formatTime(ms: number): string {
+  const total = Math.floor(ms);
+  const minutes = Math.floor(total / 60000);
+  const seconds = Math.floor((total % 60000) / 1000);
+  return `${minutes}:${String(seconds).padStart(2, "0")}.${String(total % 1000).padStart(3, "0")}`;
+}
+
+function renderLines(lines: number) {
+  const goal = tetris.get_line_goal();
+  const garbage = tetris.get_garbage_rows();
+  linesEl.textContent = goal > 0
+    ? `${lines}/${goal}`
+    : garbage > 0 ? `${lines} (${garbage} left)` : String(lines);
+  // Ultra counts down, other modes count up
+  timeEl.textContent = formatTime(tetris.get_time_remaining_ms() ?? tetris.get_elapsed_ms());
+  renderStats();
+}
+
+interface Statistics {
+  pieces: number;
+  keys: number;
+  // Locked pieces by type, in PIECE_NAMES order
+  piece_counts: number[];
+  // Locks by clear kind code: 1-4 single to Tetris, 5-11 T-spins
+  clears: number[];
+  lines: number;
+  max_combo: number;
+  time_ms: number;
+  pps: number;
+  kpp: number;
+  lpm: number;
+}
+
+const PIECE_NAMES = ["I", "O", "T", "S", "Z", "J", "L"];
+
+// Live rates while playing; the full breakdown once the game has ended
+function renderStats() {
+  const stats: Statistics = JSON.parse(tetris.get_statistics());
+  const rows: [string, string][] = [
+    ["PPS", stats.pps.toFixed(2)],
+    ["KPP", stats.kpp.toFixed(2)],
+    ["LPM", stats.lpm.toFixed(1)],
+  ];
+  const state = tetris.get_state();
+  if (state === STATE_GAME_OVER || state === STATE_FINISHED) {
+    rows.unshift(["Pieces", String(stats.pieces)], ["Time", formatTime(stats.time_ms)]);
+    rows.push(
+      // Combo counts from the second consecutive clear
+      ["Max combo", String(Math.max(stats.max_combo - 1, 0))],
+      ["Singles", String(stats.clears[1])],
+      ["Doubles", String(stats.clears[2])],
+      ["Triples", String(stats.clears[3])],
+      ["Tetrises", String(stats.clears[4])],
+      ["T-spins", String(stats.clears.slice(5).reduce((sum, count) => sum + count, 0))],
+      ...PIECE_NAMES.map((name, i): [string, string] => [name, String(stats.piece_counts[i])]),
+    );
+  }
+  statsEl.innerHTML = rows
+    .map(([label, value]) => `<div class="stat-row"><span>${label}</span><span>${value}</span></div>`)
+    .join("");
+}
+
+// Master grade, empty in other modes
+function gradeSummary(): string {
+  const grade = tetris.get_grade();
+  return grade === undefined ? "" : `Grade ${grade}`;
+}
+
+// Result line for a finished game: the score for Ultra, the time for Sprint, Dig and Master
+function finishSummary(): string {
+  const result = tetris.get_time_remaining_ms() === undefined
+    ? formatTime(tetris.get_elapsed_ms())
+    : `${tetris.get_score().toLocaleString()} pts`;
+  const grade = gradeSummary();
+  return `${grade ? `${grade} · ` : ""}${result} · ${tetris.get_pieces()} pieces · ${tetris.get_pps().toFixed(2)} PPS`;
+}
+
+const SCORING_RULES: Record<string, number> = { guideline: 0, nes: 1, tgm: 2 };
+
+// Optional ?scoring=<name> URL parameter
+function applyScoringParam(setScoring: (kind: number) => boolean) {
+  const name = new URLSearchParams(window.location.search).get("scoring");
+  if (name !== null && !(name in SCORING_RULES && setScoring(SCORING_RULES[name]))) {
+    console.warn(`Unknown scoring rules: ${name}`);
+  }
+}
+
+const ENTRY_DELAYS: Record<string, number> = { none: 0, guideline: 1, nes: 2, tgm: 3 };
+
+// Optional ?delays=<name> URL parameter for ARE and line-clear delays
+function applyEntryDelayParam(setEntryDelay: (kind: number) => boolean) {
+  const name = new URLSearchParams(window.location.search).get("delays");
+  if (name !== null && !(name in ENTRY_DELAYS && setEntryDelay(ENTRY_DELAYS[name]))) {
+    console.warn(`Unknown entry delay preset: ${name}`);
+  }
+}
+
+function readPreviewCount(): number {
+  const param = new URLSearchParams(window.location.search).get("previews");
+  const count = param === null ? DEFAULT_PREVIEWS : parseInt(param, 10);
+  return Number.isNaN(count) ? DEFAULT_PREVIEWS : Math.min(Math.max(count, 0), MAX_PREVIEWS);
+}
+
+function applyBoardSizeParams(setBoardSize: (width: number, height: number) => void) {
+  const params = new URLSearchParams(window.location.search);
+  const width = parseInt(params.get("width") ?? "", 10);
+  const height = parseInt(params.get("height") ?? "", 10);
+  if (!Number.isNaN(width) || !Number.isNaN(height)) {
+    setBoardSize(Number.isNaN(width) ? boardWidth : width, Number.isNaN(height) ? boardHeight : height);
+  }
+}
+
+// Optional ?seed=<u64> URL parameter for reproducible piece sequences
+function applySeedParam(setSeed: (seed: bigint) => void) {
+  const param = new URLSearchParams(window.location.search).get("seed");
+  if (param === null) return;
+  try {
+    setSeed(BigInt.asUintN(64, BigInt(param)));
+  } catch {
+    console.warn(`Ignoring invalid seed: ${param}`);
+  }
+}
+
 async function main() {
   await init("../pkg/tetris_core_bg.wasm");
   tetris = new Tetris();
+  tetris.set_preview_count(previewCount);
+  applySeedParam((seed) => tetris.set_seed(seed));
+  applyBoardSizeParams((width, height) => tetris.set_board_size(width, height));
+  applyRandomizerParams(
+    (kind) => tetris.set_randomizer(kind),
+    (letters) => tetris.set_piece_sequence(letters),
+  );
+  applyRotationParam((kind) => tetris.set_rotation_system(kind));
+  applyEntryDelayParam((kind) => tetris.set_entry_delay(kind));
+  applyScoringParam((kind) => tetris.set_scoring(kind));
+  applyModeParam((kind, goal) => tetris.set_mode(kind, goal));
+  applyGravityParams(
+    (kind) => tetris.set_gravity(kind),
+    (gravity) => tetris.set_gravity_table(gravity),
+    (level) => tetris.set_start_level(level),
+  );
+  resumeSavedGame();
+  boardWidth = tetris.get_board_width();
+  boardHeight = tetris.get_board_height();
+  cellSize = Math.min(CELL_SIZE, Math.floor(MAX_BOARD_PIXELS / boardHeight));
 
   createUI();
   setupInputHandlers();
@@ -67,8 +301,8 @@ function createUI() {
 
   // Create SVG
   svg = document.createElementNS("http://www.w3.org/2000/svg", "svg");
-  svg.setAttribute("width", String(BOARD_WIDTH * CELL_SIZE + 6));
-  svg.setAttribute("height", String(BOARD_HEIGHT * CELL_SIZE + 2));
+  svg.setAttribute("width", String(boardWidth * cellSize + 6));
+  svg.setAttribute("height", String(boardHeight * cellSize + 2));
   svg.setAttribute("class", "tetris-board");
 
   boardGroup = document.createElementNS("http://www.w3.org/2000/svg", "g");
@@ -82,13 +316,13 @@ function createUI() {
   svg.appendChild(overlayGroup);
 
   // Draw grid background
-  for (let y = 0; y < BOARD_HEIGHT; y++) {
-    for (let x = 0; x < BOARD_WIDTH; x++) {
+  for (let y = 0; y < boardHeight; y++) {
+    for (let x = 0; x < boardWidth; x++) {
       const rect = document.createElementNS("http://www.w3.org/2000/svg", "rect");
-      rect.setAttribute("x", String(x * CELL_SIZE));
-      rect.setAttribute("y", String(y * CELL_SIZE));
-      rect.setAttribute("width", String(CELL_SIZE));
-      rect.setAttribute("height", String(CELL_SIZE));
+      rect.setAttribute("x", String(x * cellSize));
+      rect.setAttribute("y", String(y * cellSize));
+      rect.setAttribute("width", String(cellSize));
+      rect.setAttribute("height", String(cellSize));
       rect.setAttribute("fill", get_color(0));
       rect.setAttribute("stroke", "#2a2a4a");
       rect.setAttribute("stroke-width", "1");
@@ -97,8 +331,8 @@ function createUI() {
   }
 
   // Create preview SVGs
-  nextSvg = createPreviewSvg();
-  holdSvg = createPreviewSvg();
+  nextSvg = createPreviewSvg(Math.max(previewCount * PREVIEW_SLOT_ROWS - 0.5, 0));
+  holdSvg = createPreviewSvg(2);
 
   // Build layout
   const gameArea = document.createElement("div");
@@ -111,6 +345,10 @@ function createUI() {
       <div class="panel-label">HOLD</div>
       <div class="preview-container" id="hold-container"></div>
     </div>
+    <div class="panel-section">
+      <div class="panel-label">STATS</div>
+      <div id="game-stats" class="game-stats"></div>
+    </div>
     <div class="panel-section">
       <div class="panel-label">HIGH SCORES</div>
       <div id="ingame-scores" class="ingame-scores"></div>
@@ -136,6 +374,16 @@ function createUI() {
       <div class="panel-label">LINES</div>
       <div class="stat-value" id="lines">0</div>
     </div>
+    <div class="panel-section">
+      <div class="panel-label">TIME</div>
+      <div class="stat-value" id="time">0:00.000</div>
+    </div>
+    <div class="panel-section">
+      <div class="panel-label">COMBO</div>
+      <div class="stat-value" id="combo">0</div>
+      <div class="clear-label" id="clear-label"></div>
+      <div class="clear-label" id="b2b-label"></div>
+    </div>
   `;
 
   gameArea.appendChild(leftPanel);
@@ -148,13 +396,18 @@ function createUI() {
   scoreEl = document.getElementById("score")!;
   levelEl = document.getElementById("level")!;
   linesEl = document.getElementById("lines")!;
+  timeEl = document.getElementById("time")!;
+  comboEl = document.getElementById("combo")!;
+  clearLabelEl = document.getElementById("clear-label")!;
+  b2bLabelEl = document.getElementById("b2b-label")!;
   scoresEl = document.getElementById("ingame-scores")!;
+  statsEl = document.getElementById("game-stats")!;
 }
 
-function createPreviewSvg(): SVGSVGElement {
+function createPreviewSvg(rows: number): SVGSVGElement {
   const s = document.createElementNS("http://www.w3.org/2000/svg", "svg");
   s.setAttribute("width", String(4 * CELL_SIZE * 0.8));
-  s.setAttribute("height", String(2 * CELL_SIZE * 0.8));
+  s.setAttribute("height", String(rows * CELL_SIZE * 0.8));
   s.setAttribute("class", "preview-svg");
   return s;
 }
@@ -177,7 +430,60 @@ function setupInputHandlers() {
 
   window.addEventListener("blur", () => {
     tetris.on_blur();
+    saveGame();
+  });
+  window.addEventListener("pagehide", saveGame);
+
+  // S saves the replay of a finished game; dropping a replay file plays it back
+  window.addEventListener("keydown", (e) => {
+    const state = tetris.get_state();
+    if (e.code === "KeyS" && (state === STATE_GAME_OVER || state === STATE_FINISHED)) {
+      saveReplay();
+    }
   });
+  window.addEventListener("dragover", (e) => e.preventDefault());
+  window.addEventListener("drop", async (e) => {
+    e.preventDefault();
+    const file = e.dataTransfer?.files[0];
+    if (file && !tetris.import_replay(new Uint8Array(await file.arrayBuffer()))) {
+      console.warn("Not a replay file:", file.name);
+    }
+  });
+}
+
+// Offer to resume the game saved when the page was last left
+function resumeSavedGame() {
+  const snapshot = localStorage.getItem(SNAPSHOT_KEY);
+  localStorage.removeItem(SNAPSHOT_KEY);
+  if (snapshot && confirm("Resume your saved game?") && !tetris.load_snapshot(snapshot)) {
+    console.warn("Saved game is from an older version and can't be resumed");
+  }
+}
+
+// Keep a game in progress for resuming after a reload (it comes back paused)
+function saveGame() {
+  const state = tetris.get_state();
+  if (state === STATE_PLAYING || state === STATE_PAUSED) {
+    localStorage.setItem(SNAPSHOT_KEY, tetris.save_snapshot());
+  } else {
+    localStorage.removeItem(SNAPSHOT_KEY);
+  }
+}
+
+function replayBase64(): string {
+  let binary = "";
+  for (const byte of tetris.export_replay()) binary += String.fromCharCode(byte);
+  return btoa(binary);
+}
+
+function saveReplay() {
+  const bytes = tetris.export_replay();
+  if (bytes.length === 0) return;
+  const link = document.createElement("a");
+  link.href = URL.createObjectURL(new Blob([bytes], { type: "application/octet-stream" }));
+  link.download = `tetris-${Date.now()}.replay`;
+  link.click();
+  URL.revokeObjectURL(link.href);
 }
 
 function gameLoop(currentTime: number) {
@@ -190,8 +496,12 @@ function gameLoop(currentTime: number) {
   // Handle state transitions
   if (state !== lastState) {
     if (state === STATE_GAME_OVER) {
-      showGameOver();
-      handleGameOver();
+      showGameOver("GAME OVER", gradeSummary());
+      renderStats();
+      if (!tetris.is_replaying() && !tetris.is_practice() && tetris.is_standard()) handleGameOver();
+    } else if (state === STATE_FINISHED) {
+      render();
+      showGameOver(tetris.get_time_remaining_ms() === undefined ? "FINISHED" : "TIME UP", finishSummary());
     } else if (state === STATE_PAUSED) {
       showPauseOverlay();
     } else if (state === STATE_PLAYING && lastState !== STATE_PLAYING) {
@@ -206,6 +516,10 @@ function gameLoop(currentTime: number) {
     render();
   }
 
+  if (tetris.take_perfect_clear()) {
+    showPerfectClear();
+  }
+
   requestAnimationFrame(gameLoop);
 }
 
@@ -213,7 +527,8 @@ function render() {
   // Update stats
   scoreEl.textContent = String(tetris.get_score());
   levelEl.textContent = String(tetris.get_level());
-  linesEl.textContent = String(tetris.get_lines());
+  renderLines(tetris.get_lines());
+  renderClearInfo(tetris.get_combo(), tetris.is_back_to_back(), tetris.get_last_clear());
 
   // Clear dynamic groups
   ghostGroup.innerHTML = "";
@@ -229,23 +544,30 @@ function render() {
     const x = boardCells[i];
     const y = boardCells[i + 1];
     const color = boardCells[i + 2];
-    const idx = y * BOARD_WIDTH + x;
+    const idx = y * boardWidth + x;
     boardRects[idx]?.setAttribute("fill", get_color(color));
   }
 
   // Render ghost
   const ghostCells = tetris.get_ghost_cells();
-  renderCells(ghostGroup, ghostCells, CELL_SIZE);
+  renderCells(ghostGroup, ghostCells, cellSize);
 
   // Render piece
   const pieceCells = tetris.get_piece_cells();
-  renderCells(pieceGroup, pieceCells, CELL_SIZE);
+  renderCells(pieceGroup, pieceCells, cellSize);
 
   // Render previews
-  renderPreview(nextSvg, tetris.get_next_cells());
+  renderQueue(nextSvg);
   renderPreview(holdSvg, tetris.get_hold_cells());
 }
 
+function renderClearInfo(combo: number, backToBack: boolean, lastClear: number) {
+  // Combo counts from the second consecutive clear
+  comboEl.textContent = String(Math.max(combo - 1, 0));
+  clearLabelEl.textContent = get_clear_name(lastClear);
+  b2bLabelEl.textContent = backToBack ? "BACK-TO-BACK" : "";
+}
+
 function renderCells(group: SVGGElement, cells: Uint8Array, cellSize: number) {
   for (let i = 0; i < cells.length; i += 4) {
     const x = cells[i];
@@ -267,10 +589,23 @@ function renderCells(group: SVGGElement, cells: Uint8Array, cellSize: number) {
 
 function renderPreview(svg: SVGSVGElement, cells: Uint8Array) {
   svg.innerHTML = "";
+  appendPreviewCells(svg, cells, 0);
+}
+
+// Next queue as a vertical stack, nearest piece on top
+function renderQueue(svg: SVGSVGElement) {
+  svg.innerHTML = "";
+  const slots = tetris.get_queue_length();
+  for (let slot = 0; slot < slots; slot++) {
+    appendPreviewCells(svg, tetris.get_queue_cells(slot), slot * PREVIEW_SLOT_ROWS);
+  }
+}
+
+function appendPreviewCells(svg: SVGSVGElement, cells: Uint8Array, rowOffset: number) {
   const cellSize = CELL_SIZE * 0.8;
   for (let i = 0; i < cells.length; i += 4) {
     const x = cells[i];
-    const y = cells[i + 1];
+    const y = cells[i + 1] + rowOffset;
     const color = cells[i + 2];
     const opacity = cells[i + 3] / 255;
 
@@ -303,8 +638,10 @@ function showStartScreen() {
           <li><kbd>Space</kbd> Hard drop</li>
           <li><kbd>&uarr;</kbd> <kbd>X</kbd> Rotate CW</li>
           <li><kbd>Z</kbd> Rotate CCW</li>
+          <li><kbd>A</kbd> Rotate 180</li>
           <li><kbd>C</kbd> <kbd>Shift</kbd> Hold</li>
           <li><kbd>P</kbd> <kbd>Esc</kbd> Pause</li>
+          <li><kbd>U</kbd> <kbd>Y</kbd> Undo / redo (practice)</li>
         </ul>
       </div>
       <div id="high-scores"></div>
@@ -331,34 +668,55 @@ function showPauseOverlay() {
   overlay.style.display = "flex";
 }
 
-function showGameOver() {
+function showGameOver(title = "GAME OVER", detail = "") {
   const rect = document.createElementNS("http://www.w3.org/2000/svg", "rect");
-  rect.setAttribute("width", String(BOARD_WIDTH * CELL_SIZE));
-  rect.setAttribute("height", String(BOARD_HEIGHT * CELL_SIZE));
+  rect.setAttribute("width", String(boardWidth * cellSize));
+  rect.setAttribute("height", String(boardHeight * cellSize));
   rect.setAttribute("fill", "rgba(0,0,0,0.7)");
   overlayGroup.appendChild(rect);
 
   const text = document.createElementNS("http://www.w3.org/2000/svg", "text");
-  text.setAttribute("x", String((BOARD_WIDTH * CELL_SIZE) / 2));
-  text.setAttribute("y", String((BOARD_HEIGHT * CELL_SIZE) / 2));
+  text.setAttribute("x", String((boardWidth * cellSize) / 2));
+  text.setAttribute("y", String((boardHeight * cellSize) / 2));
   text.setAttribute("text-anchor", "middle");
   text.setAttribute("fill", "#fff");
   text.setAttribute("font-size", "24");
   text.setAttribute("font-family", "monospace");
-  text.textContent = "GAME OVER";
+  text.textContent = title;
   overlayGroup.appendChild(text);
 
+  if (detail) {
+    const detailText = document.createElementNS("http://www.w3.org/2000/svg", "text");
+    detailText.setAttribute("x", String((boardWidth * cellSize) / 2));
+    detailText.setAttribute("y", String((boardHeight * cellSize) / 2 - 30));
+    detailText.setAttribute("text-anchor", "middle");
+    detailText.setAttribute("fill", "#fff");
+    detailText.setAttribute("font-size", "14");
+    detailText.setAttribute("font-family", "monospace");
+    detailText.textContent = detail;
+    overlayGroup.appendChild(detailText);
+  }
+
   const subtext = document.createElementNS("http://www.w3.org/2000/svg", "text");
-  subtext.setAttribute("x", String((BOARD_WIDTH * CELL_SIZE) / 2));
-  subtext.setAttribute("y", String((BOARD_HEIGHT * CELL_SIZE) / 2 + 30));
+  subtext.setAttribute("x", String((boardWidth * cellSize) / 2));
+  subtext.setAttribute("y", String((boardHeight * cellSize) / 2 + 30));
   subtext.setAttribute("text-anchor", "middle");
   subtext.setAttribute("fill", "#888");
   subtext.setAttribute("font-size", "14");
   subtext.setAttribute("font-family", "monospace");
-  subtext.textContent = "Press R to restart";
+  subtext.textContent = "Press R to restart, S to save replay";
   overlayGroup.appendChild(subtext);
 }
 
+function showPerfectClear() {
+  document.getElementById("perfect-clear")?.remove();
+  const banner = document.createElement("div");
+  banner.id = "perfect-clear";
+  banner.textContent = "PERFECT CLEAR";
+  banner.addEventListener("animationend", () => banner.remove());
+  document.getElementById("game-container")?.appendChild(banner);
+}
+
 function clearOverlay() {
   overlayGroup.innerHTML = "";
   document.getElementById("start-screen")?.style.setProperty("display", "none");
@@ -366,7 +724,7 @@ function clearOverlay() {
 }
 
 async function loadHighScores() {
-  const scores = await getHighScores();
+  const scores = await getHighScores(tetris.get_ruleset());
   displayHighScores(scores);
   displayInGameScores(scores);
 }
@@ -410,13 +768,14 @@ async function handleGameOver() {
   const score = tetris.get_score();
   const level = tetris.get_level();
   const lines = tetris.get_lines();
-  const scores = await getHighScores();
+  const ruleset = tetris.get_ruleset();
+  const scores = await getHighScores(ruleset);
   const isHighScore = scores.length < 10 || score > (scores[9]?.score ?? 0);
 
   if (isHighScore && score > 0) {
     const name = prompt(`High Score! Enter your name (Score: ${score.toLocaleString()})`);
     if (name?.trim()) {
-      await submitScore({ name: name.trim().substring(0, 20), score, level, lines });
+      await submitScore({ name: name.trim().substring(0, 20), score, level, lines, ruleset, replay: replayBase64() });
       await loadHighScores();
     }
   }
@@ -6,34 +6,82 @@ interface Score {
   score: number;
   level: number;
   lines: number;
+  // Scoring ruleset the game was played under; older entries have none and count as guideline
+  ruleset?: string;
   timestamp: number;
 }
 
+const DEFAULT_RULESET = "guideline";
+
+function rulesetOf(score: Score): string {
+  return score.ruleset ?? DEFAULT_RULESET;
+}
+
 // Use Deno KV for persistent storage
 const kv = await Deno.openKv();
 
-async function loadScores(): Promise<Score[]> {
+// Every score on the main leaderboard comes with its game's replay. With TETRIS_VERIFY_LIB
+// pointing at the tetris-verify library, it is only accepted if the replay re-simulates to
+// exactly that result under the standard settings. Without the library, scores are refused
+// unless ALLOW_UNVERIFIED_SCORES=1 opts into taking replays unchecked
+const verifyLibPath = Deno.env.get("TETRIS_VERIFY_LIB");
+const verifier = verifyLibPath
+  ? Deno.dlopen(verifyLibPath, {
+    // Nonblocking: re-simulating runs on another thread while other requests are served
+    verify_replay: { parameters: ["buffer", "usize", "buffer"], result: "i32", nonblocking: true },
+  })
+  : null;
+const allowUnverified = Deno.env.get("ALLOW_UNVERIFIED_SCORES") === "1";
+if (!verifier) {
+  console.warn(
+    allowUnverified
+      ? "TETRIS_VERIFY_LIB is not set: accepting scores without checking their replays"
+      : "TETRIS_VERIFY_LIB is not set: refusing scores (set ALLOW_UNVERIFIED_SCORES=1 to accept them unchecked)",
+  );
+}
+
+async function replayMatches(replay: string, score: Omit<Score, "timestamp">): Promise<boolean> {
+  let bytes: Uint8Array;
+  try {
+    bytes = Uint8Array.from(atob(replay), (c) => c.charCodeAt(0));
+  } catch {
+    return false;
+  }
+  if (!verifier) return allowUnverified && bytes.length > 0;
+  const { score: points, level, lines, ruleset } = score;
+  const claim = new TextEncoder().encode(JSON.stringify({ score: points, level, lines, ruleset }) + "\0");
+  return (await verifier.symbols.verify_replay(bytes, bytes.length, claim)) === 1;
+}
+
+// Main leaderboard of replay-backed scores, and a separate one for games without replays
+// (diox-tetris), whose scores are taken on trust
+const VERIFIED = "scores";
+const UNVERIFIED = "unverified-scores";
+type Leaderboard = typeof VERIFIED | typeof UNVERIFIED;
+
+async function loadScores(board: Leaderboard): Promise<Score[]> {
   const scores: Score[] = [];
-  const entries = kv.list<Score>({ prefix: ["scores"] });
+  const entries = kv.list<Score>({ prefix: [board] });
   for await (const entry of entries) {
     scores.push(entry.value);
   }
   return scores;
 }
 
-async function saveScore(score: Score): Promise<void> {
-  await kv.set(["scores", score.timestamp], score);
+async function saveScore(board: Leaderboard, score: Score): Promise<void> {
+  await kv.set([board, score.timestamp], score);
 
-  const scores = await loadScores();
+  // Keep the best 100 of each ruleset
+  const scores = (await loadScores(board)).filter((s) => rulesetOf(s) === rulesetOf(score));
   scores.sort((a, b) => b.score - a.score);
   if (scores.length > 100) {
     for (const s of scores.slice(100)) {
-      await kv.delete(["scores", s.timestamp]);
+      await kv.delete([board, s.timestamp]);
     }
   }
 }
 
-function isValidScore(obj: unknown): obj is Omit<Score, "timestamp"> {
+function isValidScore(obj: unknown): obj is Omit<Score, "timestamp"> & { replay?: unknown } {
   if (typeof obj !== "object" || obj === null) return false;
   const s = obj as Record<string, unknown>;
   return (
@@ -45,7 +93,8 @@ function isValidScore(obj: unknown): obj is Omit<Score, "timestamp"> {
     typeof s.level === "number" &&
     s.level >= 1 &&
     typeof s.lines === "number" &&
-    s.lines >= 0
+    s.lines >= 0 &&
+    (s.ruleset === undefined || (typeof s.ruleset === "string" && /^[a-z0-9-]{1,20}$/.test(s.ruleset)))
   );
 }
 
@@ -71,15 +120,23 @@ async function handler(req: Request): Promise<Response> {
     return new Response(null, { status: 204, headers: CORS_HEADERS });
   }
 
-  // API: Get scores
-  if (path === "/api/scores" && req.method === "GET") {
-    const scores = await loadScores();
+  // /api/scores is the main leaderboard, /api/scores/unverified the one for games without replays
+  const board: Leaderboard | null = path === "/api/scores"
+    ? VERIFIED
+    : path === "/api/scores/unverified"
+    ? UNVERIFIED
+    : null;
+
+  // API: Get scores (?ruleset=<name>, guideline by default)
+  if (board && req.method === "GET") {
+    const ruleset = url.searchParams.get("ruleset") ?? DEFAULT_RULESET;
+    const scores = (await loadScores(board)).filter((s) => rulesetOf(s) === ruleset);
     scores.sort((a, b) => b.score - a.score);
     return Response.json(scores.slice(0, 10), { headers: CORS_HEADERS });
   }
 
   // API: Submit score
-  if (path === "/api/scores" && req.method === "POST") {
+  if (board && req.method === "POST") {
     try {
       const body = await req.json();
       if (!isValidScore(body)) {
@@ -93,9 +150,31 @@ async function handler(req: Request): Promise<Response> {
         score: Math.floor(body.score),
         level: Math.floor(body.level),
         lines: Math.floor(body.lines),
+        ruleset: body.ruleset ?? DEFAULT_RULESET,
         timestamp: Date.now(),
       };
-      await saveScore(score);
+      if (board === VERIFIED) {
+        if (!verifier && !allowUnverified) {
+          return Response.json(
+            { error: "Score verification is unavailable" },
+            { status: 503, headers: CORS_HEADERS }
+          );
+        }
+        // Base64 replay from the game's export_replay
+        if (typeof body.replay !== "string") {
+          return Response.json(
+            { error: "Invalid score data" },
+            { status: 400, headers: CORS_HEADERS }
+          );
+        }
+        if (!(await replayMatches(body.replay, score))) {
+          return Response.json(
+            { error: "Replay does not match score" },
+            { status: 422, headers: CORS_HEADERS }
+          );
+        }
+      }
+      await saveScore(board, score);
       return Response.json(
         { success: true },
         { status: 201, headers: CORS_HEADERS }
@@ -3,13 +3,25 @@ use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlCanvasElement;
 
-use crate::controller::{GameController, GameState};
+use crate::board::{MAX_HEIGHT, MAX_WIDTH, MIN_HEIGHT, MIN_WIDTH};
+use crate::controller::{EntryDelay, GameController, GameState};
+use crate::events::encode_events;
+use crate::game::{MAX_PREVIEWS, MAX_START_LEVEL};
+use crate::gravity::GravityKind;
+use crate::mode::GameMode;
+use crate::randomizer::{PieceRandomizer, ScriptedRandomizer};
+use crate::replay::Replay;
+use crate::rotation::RotationKind;
+use crate::scoring::ScoringKind;
 use crate::webgl::{PreviewRenderer, WebGlRenderer};
+use crate::{KEY_REDO, KEY_ROTATE_180, KEY_UNDO};
 
 const CELL_SIZE: f32 = 30.0;
 const PREVIEW_CELL_SIZE: f32 = CELL_SIZE * 0.8;
-const BOARD_WIDTH: u32 = 10;
-const BOARD_HEIGHT: u32 = 20;
+/// Board cells shrink below CELL_SIZE so tall boards still fit this height
+const MAX_BOARD_PIXELS: f32 = 600.0;
+
+type FrameCallback = Closure<dyn FnMut(f64)>;
 
 #[wasm_bindgen]
 pub struct TetrisApp {
@@ -25,6 +37,7 @@ struct TetrisAppInner {
     last_state: GameState,
     on_state_change: Option<js_sys::Function>,
     on_score_update: Option<js_sys::Function>,
+    on_perfect_clear: Option<js_sys::Function>,
     animation_id: Option<i32>,
 }
 
@@ -36,15 +49,20 @@ impl TetrisApp {
         next_canvas: HtmlCanvasElement,
         hold_canvas: HtmlCanvasElement,
     ) -> Result<TetrisApp, JsValue> {
-        let mut board_renderer =
-            WebGlRenderer::new(&board_canvas, BOARD_WIDTH, BOARD_HEIGHT, CELL_SIZE)?;
-        board_renderer.set_grid_offset(3.0, 1.0);
+        let controller = GameController::new();
+        let (width, height) = (controller.config.width, controller.config.height);
+        let board_renderer = WebGlRenderer::new(
+            &board_canvas,
+            width as u32,
+            height as u32,
+            board_cell_size(height),
+        )?;
 
         let next_renderer = PreviewRenderer::new(&next_canvas, PREVIEW_CELL_SIZE)?;
         let hold_renderer = PreviewRenderer::new(&hold_canvas, PREVIEW_CELL_SIZE)?;
 
         let inner = Rc::new(RefCell::new(TetrisAppInner {
-            controller: GameController::new(),
+            controller,
             board_renderer,
             next_renderer,
             hold_renderer,
@@ -52,6 +70,7 @@ impl TetrisApp {
             last_state: GameState::Idle,
             on_state_change: None,
             on_score_update: None,
+            on_perfect_clear: None,
             animation_id: None,
         }));
 
@@ -66,6 +85,10 @@ impl TetrisApp {
         self.inner.borrow_mut().on_score_update = Some(callback);
     }
 
+    pub fn set_on_perfect_clear(&mut self, callback: js_sys::Function) {
+        self.inner.borrow_mut().on_perfect_clear = Some(callback);
+    }
+
     pub fn start_game_loop(&self) -> Result<(), JsValue> {
         let inner = self.inner.clone();
 
@@ -77,7 +100,7 @@ impl TetrisApp {
         }
 
         // Set up the game loop
-        let f: Rc<RefCell<Option<Closure<dyn FnMut(f64)>>>> = Rc::new(RefCell::new(None));
+        let f: Rc<RefCell<Option<FrameCallback>>> = Rc::new(RefCell::new(None));
         let g = f.clone();
         let inner_clone = inner.clone();
 
@@ -114,8 +137,15 @@ impl TetrisApp {
                 None
             };
 
-            // Render if playing
-            if current_state == GameState::Playing {
+            // Collect perfect clear callback
+            let perfect_clear_callback = if app.controller.take_perfect_clear() {
+                app.on_perfect_clear.clone()
+            } else {
+                None
+            };
+
+            // Render while playing, and once more on a state change to show the final board
+            if current_state == GameState::Playing || state_changed {
                 app.render();
             }
 
@@ -140,6 +170,10 @@ impl TetrisApp {
                 );
             }
 
+            if let Some(callback) = perfect_clear_callback {
+                let _ = callback.call0(&JsValue::NULL);
+            }
+
             // Request next frame
             let window = web_sys::window().unwrap();
             let _ = window.request_animation_frame(
@@ -177,6 +211,260 @@ impl TetrisApp {
         self.inner.borrow_mut().controller.on_blur();
     }
 
+    /// Events since the last call as `[code, length, payload...]` records
+    /// (see `GameEvent::encode`)
+    pub fn drain_events(&self) -> Vec<i32> {
+        encode_events(&self.inner.borrow_mut().controller.drain_events())
+    }
+
+    /// Use a fixed seed for every following start/restart
+    pub fn set_seed(&self, seed: u64) {
+        self.inner.borrow_mut().controller.config.seed = Some(seed);
+    }
+
+    pub fn clear_seed(&self) {
+        self.inner.borrow_mut().controller.config.seed = None;
+    }
+
+    /// Board size for following games, clamped to 4x4 up to 20x40.
+    /// Resizes the board canvas; read the clamped size back with the getters.
+    pub fn set_board_size(&self, width: usize, height: usize) {
+        let mut app = self.inner.borrow_mut();
+        let width = width.clamp(MIN_WIDTH, MAX_WIDTH);
+        let height = height.clamp(MIN_HEIGHT, MAX_HEIGHT);
+        app.controller.config.width = width;
+        app.controller.config.height = height;
+        app.board_renderer
+            .set_grid_size(width as u32, height as u32, board_cell_size(height));
+    }
+
+    pub fn get_board_width(&self) -> usize {
+        self.inner.borrow().board_renderer.grid_size().0 as usize
+    }
+
+    pub fn get_board_height(&self) -> usize {
+        self.inner.borrow().board_renderer.grid_size().1 as usize
+    }
+
+    /// Next queue length (0-6) for following games; size the next canvas to match
+    pub fn set_preview_count(&self, count: usize) {
+        self.inner.borrow_mut().controller.config.preview_count = count.min(MAX_PREVIEWS);
+    }
+
+    /// Piece generator for following games (see `PieceRandomizer::from_u8`)
+    pub fn set_randomizer(&self, kind: u8) -> bool {
+        match PieceRandomizer::from_u8(kind) {
+            Some(randomizer) => {
+                self.inner.borrow_mut().controller.config.randomizer = randomizer;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Rotation system for following games (see `RotationKind::from_u8`)
+    pub fn set_rotation_system(&self, kind: u8) -> bool {
+        match RotationKind::from_u8(kind) {
+            Some(rotation) => {
+                self.inner.borrow_mut().controller.config.rotation = rotation;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Scoring rules for following games (see `ScoringKind::from_u8`)
+    pub fn set_scoring(&self, kind: u8) -> bool {
+        match ScoringKind::from_u8(kind) {
+            Some(scoring) => {
+                self.inner.borrow_mut().controller.config.scoring = scoring;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Scoring ruleset name of the current game, to tag high scores with
+    pub fn get_ruleset(&self) -> String {
+        self.inner.borrow().controller.scoring().name().to_string()
+    }
+
+    /// Gravity curve for following games (see `GravityKind::from_u8`)
+    pub fn set_gravity(&self, kind: u8) -> bool {
+        match GravityKind::from_u8(kind) {
+            Some(gravity) => {
+                self.inner.borrow_mut().controller.config.gravity = gravity;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Custom gravity table for following games, in G per level from level 1
+    pub fn set_gravity_table(&self, gravity: Vec<f64>) -> bool {
+        match GravityKind::table(&gravity) {
+            Some(gravity) => {
+                self.inner.borrow_mut().controller.config.gravity = gravity;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Level following games start at (1 to 30)
+    pub fn set_start_level(&self, level: u32) {
+        self.inner.borrow_mut().controller.config.start_level = level.clamp(1, MAX_START_LEVEL);
+    }
+
+    /// Delays between pieces for following games (see `EntryDelay::from_u8`)
+    pub fn set_entry_delay(&self, kind: u8) -> bool {
+        match EntryDelay::from_u8(kind) {
+            Some(delay) => {
+                self.inner.borrow_mut().controller.entry_delay = delay;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Fixed, repeating piece sequence for following games, e.g. "TIOLJSZ"
+    pub fn set_piece_sequence(&self, letters: &str) -> bool {
+        match ScriptedRandomizer::parse(letters) {
+            Some(script) => {
+                self.inner.borrow_mut().controller.config.randomizer =
+                    PieceRandomizer::Scripted(script);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.inner
+            .borrow()
+            .controller
+            .game
+            .as_ref()
+            .map(|g| g.seed)
+            .unwrap_or(0)
+    }
+
+    /// Mode of following games: 0=Marathon, 1=Sprint with a `goal` of 20, 40 or 100 lines
+    /// (0 for 40), 2=Ultra, 3=Dig with a `goal` of 5, 10 or 18 garbage rows (0 for 10),
+    /// 4=Dig with rising garbage, 5=Master (20G, graded)
+    pub fn set_mode(&self, kind: u8, goal: u32) -> bool {
+        match GameMode::from_u8(kind, goal) {
+            Some(mode) => {
+                self.inner.borrow_mut().controller.config.mode = mode;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Play time of the current game in ms, not counting pauses
+    pub fn get_elapsed_ms(&self) -> f64 {
+        self.inner.borrow().controller.elapsed_ms()
+    }
+
+    /// Play time left in Ultra, frozen while paused
+    pub fn get_time_remaining_ms(&self) -> Option<f64> {
+        self.inner.borrow().controller.time_remaining_ms()
+    }
+
+    pub fn get_pieces(&self) -> u32 {
+        self.inner.borrow().controller.pieces()
+    }
+
+    pub fn get_pps(&self) -> f64 {
+        self.inner.borrow().controller.pps()
+    }
+
+    /// Lines to clear to finish the current game (0 = no line goal)
+    pub fn get_line_goal(&self) -> u32 {
+        self.inner.borrow().controller.mode().line_goal().unwrap_or(0)
+    }
+
+    /// Garbage rows left to clear in Dig
+    pub fn get_garbage_rows(&self) -> u32 {
+        self.inner.borrow().controller.garbage_rows()
+    }
+
+    /// Master grade earned so far ("9" up to "S9", or "GM"; undefined in other modes)
+    pub fn get_grade(&self) -> Option<String> {
+        self.inner.borrow().controller.grade().map(str::to_string)
+    }
+
+    /// Play time of each Master section finished so far, in ms
+    pub fn get_section_times(&self) -> Vec<f64> {
+        self.inner.borrow().controller.section_times().to_vec()
+    }
+
+    /// Recording of the current (or last) game, empty before the first one
+    pub fn export_replay(&self) -> Vec<u8> {
+        self.inner
+            .borrow()
+            .controller
+            .replay()
+            .map(Replay::to_bytes)
+            .unwrap_or_default()
+    }
+
+    /// Play back a recording from `export_replay`; false if it can't be read
+    pub fn import_replay(&self, bytes: &[u8]) -> bool {
+        match Replay::from_bytes(bytes) {
+            Some(replay) => {
+                self.inner.borrow_mut().controller.play_replay(&replay);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the current game is a replay being played back
+    pub fn is_replaying(&self) -> bool {
+        self.inner.borrow().controller.is_replaying()
+    }
+
+    /// Whether undo was used this game; practice games can't go on the leaderboard
+    pub fn is_practice(&self) -> bool {
+        self.inner.borrow().controller.is_practice()
+    }
+
+    /// Whether the current (or last) game was played under the standard
+    /// settings the leaderboards rank (see `Replay::is_standard`)
+    pub fn is_standard(&self) -> bool {
+        self.inner
+            .borrow()
+            .controller
+            .replay()
+            .is_some_and(Replay::is_standard)
+    }
+
+    /// Totals of the current (or last) game as JSON: pieces, keys, piece_counts
+    /// (I, O, T, S, Z, J, L), clears (by clear kind code), lines, max_combo,
+    /// time_ms, pps, kpp and lpm
+    pub fn get_statistics(&self) -> String {
+        self.inner.borrow().controller.statistics().to_json()
+    }
+
+    /// Versioned JSON of the whole game state, for resuming after a reload
+    pub fn save_snapshot(&self) -> String {
+        self.inner.borrow().controller.save_snapshot()
+    }
+
+    /// Restore a `save_snapshot` (a game in progress comes back paused);
+    /// false for anything else, including snapshots of an older version
+    pub fn load_snapshot(&self, json: &str) -> bool {
+        match GameController::load_snapshot(json) {
+            Some(controller) => {
+                self.inner.borrow_mut().controller = controller;
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn get_state(&self) -> u8 {
         state_to_u8(self.inner.borrow().controller.state)
     }
@@ -211,6 +499,36 @@ impl TetrisApp {
             .unwrap_or(0)
     }
 
+    pub fn get_combo(&self) -> u32 {
+        self.inner
+            .borrow()
+            .controller
+            .game
+            .as_ref()
+            .map(|g| g.combo)
+            .unwrap_or(0)
+    }
+
+    pub fn is_back_to_back(&self) -> bool {
+        self.inner
+            .borrow()
+            .controller
+            .game
+            .as_ref()
+            .map(|g| g.back_to_back)
+            .unwrap_or(false)
+    }
+
+    pub fn get_last_clear(&self) -> u8 {
+        self.inner
+            .borrow()
+            .controller
+            .game
+            .as_ref()
+            .map(|g| g.last_clear as u8)
+            .unwrap_or(0)
+    }
+
     pub fn render_initial(&self) {
         let app = self.inner.borrow();
         app.board_renderer.clear();
@@ -227,15 +545,22 @@ impl TetrisAppInner {
             .unwrap_or((0, 1, 0))
     }
 
-    fn render(&self) {
-        // Clear and render grid
-        self.board_renderer.clear();
-        self.board_renderer.render_grid();
-
+    fn render(&mut self) {
         // Get render state
         let render_state = self.controller.get_render_state();
         let arrays = render_state.to_flat_arrays();
 
+        // Follow the current game's board size
+        let (width, height) = (arrays.width as u32, arrays.height as u32);
+        if self.board_renderer.grid_size() != (width, height) {
+            self.board_renderer
+                .set_grid_size(width, height, board_cell_size(height as usize));
+        }
+
+        // Clear and render grid
+        self.board_renderer.clear();
+        self.board_renderer.render_grid();
+
         // Render board cells
         self.board_renderer.render_cells(&arrays.board, 3.0);
 
@@ -246,11 +571,15 @@ impl TetrisAppInner {
         self.board_renderer.render_cells(&arrays.piece, 3.0);
 
         // Render previews
-        self.next_renderer.render_cells(&arrays.next, 2.0);
+        self.next_renderer.render_stack(&arrays.queue, 2.0);
         self.hold_renderer.render_cells(&arrays.hold, 2.0);
     }
 }
 
+fn board_cell_size(height: usize) -> f32 {
+    CELL_SIZE.min((MAX_BOARD_PIXELS / height as f32).floor())
+}
+
 fn key_code_to_u8(code: &str) -> Option<u8> {
     match code {
         "ArrowLeft" => Some(0),
@@ -263,6 +592,9 @@ fn key_code_to_u8(code: &str) -> Option<u8> {
         "KeyP" | "Escape" => Some(7),
         "Enter" => Some(8),
         "KeyR" => Some(9),
+        "KeyA" => Some(KEY_ROTATE_180),
+        "KeyU" => Some(KEY_UNDO),
+        "KeyY" => Some(KEY_REDO),
         _ => None,
     }
 }
@@ -273,5 +605,6 @@ fn state_to_u8(state: GameState) -> u8 {
         GameState::Playing => 1,
         GameState::Paused => 2,
         GameState::GameOver => 3,
+        GameState::Finished => 4,
     }
 }
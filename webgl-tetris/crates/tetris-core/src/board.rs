@@ -1,9 +1,16 @@
 use serde::{Deserialize, Serialize};
 
-pub const WIDTH: usize = 10;
-pub const HEIGHT: usize = 20;
+/// Default (guideline) board size
+pub const DEFAULT_WIDTH: usize = 10;
+pub const DEFAULT_HEIGHT: usize = 20;
 pub const BUFFER_HEIGHT: usize = 4; // Hidden rows above visible area
 
+/// Supported board sizes, from 4-wide combo boards to 20x40 big boards
+pub const MIN_WIDTH: usize = 4;
+pub const MAX_WIDTH: usize = 20;
+pub const MIN_HEIGHT: usize = 4;
+pub const MAX_HEIGHT: usize = 40;
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum Cell {
@@ -15,6 +22,8 @@ pub enum Cell {
     Z = 5,
     J = 6,
     L = 7,
+    /// Rows pushed up from below in Dig mode
+    Garbage = 8,
 }
 
 impl Cell {
@@ -24,9 +33,42 @@ impl Cell {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "RawBoard")]
 pub struct Board {
-    // Grid stored as row-major, bottom row is index 0
-    grid: [[Cell; WIDTH]; HEIGHT + BUFFER_HEIGHT],
+    width: usize,
+    height: usize,
+    // Grid stored as row-major, bottom row is index 0, height + BUFFER_HEIGHT rows
+    grid: Vec<Vec<Cell>>,
+}
+
+/// Deserialized form of `Board`, checked so the grid matches the board size
+#[derive(Deserialize)]
+struct RawBoard {
+    width: usize,
+    height: usize,
+    grid: Vec<Vec<Cell>>,
+}
+
+impl TryFrom<RawBoard> for Board {
+    type Error = &'static str;
+
+    fn try_from(raw: RawBoard) -> Result<Self, Self::Error> {
+        if !(MIN_WIDTH..=MAX_WIDTH).contains(&raw.width)
+            || !(MIN_HEIGHT..=MAX_HEIGHT).contains(&raw.height)
+        {
+            return Err("board size is out of the supported range");
+        }
+        if raw.grid.len() != raw.height + BUFFER_HEIGHT
+            || raw.grid.iter().any(|row| row.len() != raw.width)
+        {
+            return Err("board grid doesn't match the board size");
+        }
+        Ok(Self {
+            width: raw.width,
+            height: raw.height,
+            grid: raw.grid,
+        })
+    }
 }
 
 impl Default for Board {
@@ -37,13 +79,40 @@ impl Default for Board {
 
 impl Board {
     pub fn new() -> Self {
+        Self::with_size(DEFAULT_WIDTH, DEFAULT_HEIGHT)
+    }
+
+    /// Create an empty board of the given visible size, clamped to the supported range
+    pub fn with_size(width: usize, height: usize) -> Self {
+        let width = width.clamp(MIN_WIDTH, MAX_WIDTH);
+        let height = height.clamp(MIN_HEIGHT, MAX_HEIGHT);
         Self {
-            grid: [[Cell::Empty; WIDTH]; HEIGHT + BUFFER_HEIGHT],
+            width,
+            height,
+            grid: vec![vec![Cell::Empty; width]; height + BUFFER_HEIGHT],
         }
     }
 
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Visible rows, not counting the hidden buffer
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn total_height(&self) -> usize {
+        self.height + BUFFER_HEIGHT
+    }
+
+    /// Where new pieces appear: centered (rounding left) in the first buffer row
+    pub fn spawn_position(&self) -> (i32, i32) {
+        (self.width as i32 / 2 - 1, self.height as i32)
+    }
+
     pub fn get(&self, x: i32, y: i32) -> Option<Cell> {
-        if x < 0 || x >= WIDTH as i32 || y < 0 || y >= (HEIGHT + BUFFER_HEIGHT) as i32 {
+        if x < 0 || x >= self.width as i32 || y < 0 || y >= self.total_height() as i32 {
             None
         } else {
             Some(self.grid[y as usize][x as usize])
@@ -51,17 +120,17 @@ impl Board {
     }
 
     pub fn set(&mut self, x: i32, y: i32, cell: Cell) {
-        if x >= 0 && x < WIDTH as i32 && y >= 0 && y < (HEIGHT + BUFFER_HEIGHT) as i32 {
+        if x >= 0 && x < self.width as i32 && y >= 0 && y < self.total_height() as i32 {
             self.grid[y as usize][x as usize] = cell;
         }
     }
 
     /// Check if a position is valid (in bounds and empty)
     pub fn is_valid_position(&self, x: i32, y: i32) -> bool {
-        if x < 0 || x >= WIDTH as i32 || y < 0 {
+        if x < 0 || x >= self.width as i32 || y < 0 {
             return false;
         }
-        if y >= (HEIGHT + BUFFER_HEIGHT) as i32 {
+        if y >= self.total_height() as i32 {
             return true; // Above the board is valid
         }
         self.grid[y as usize][x as usize].is_empty()
@@ -79,36 +148,76 @@ impl Board {
         }
     }
 
+    /// Rows (0 = bottom) that are completely filled
+    pub fn full_rows(&self) -> Vec<u32> {
+        (0..self.total_height())
+            .filter(|&row| self.grid[row].iter().all(|cell| !cell.is_empty()))
+            .map(|row| row as u32)
+            .collect()
+    }
+
     /// Clear completed lines and return count
     pub fn clear_lines(&mut self) -> u32 {
         let mut lines_cleared = 0;
         let mut write_row = 0;
 
-        for read_row in 0..(HEIGHT + BUFFER_HEIGHT) {
+        for read_row in 0..self.total_height() {
             let is_full = self.grid[read_row].iter().all(|&cell| !cell.is_empty());
 
             if is_full {
                 lines_cleared += 1;
             } else {
                 if write_row != read_row {
-                    self.grid[write_row] = self.grid[read_row];
+                    self.grid.swap(write_row, read_row);
                 }
                 write_row += 1;
             }
         }
 
         // Fill remaining rows with empty
-        while write_row < HEIGHT + BUFFER_HEIGHT {
-            self.grid[write_row] = [Cell::Empty; WIDTH];
-            write_row += 1;
+        for row in &mut self.grid[write_row..] {
+            row.fill(Cell::Empty);
         }
 
         lines_cleared
     }
 
+    /// Push the stack up by `rows` and fill the bottom `rows` rows with garbage,
+    /// leaving `hole_column` open in each. Returns false if filled cells were
+    /// pushed off the top of the buffer.
+    pub fn insert_garbage(&mut self, rows: usize, hole_column: usize) -> bool {
+        let rows = rows.min(self.total_height());
+        let fits = self.grid[self.total_height() - rows..]
+            .iter()
+            .all(|row| row.iter().all(|cell| cell.is_empty()));
+        self.grid.rotate_right(rows);
+        for row in &mut self.grid[..rows] {
+            row.fill(Cell::Garbage);
+            if let Some(hole) = row.get_mut(hole_column) {
+                *hole = Cell::Empty;
+            }
+        }
+        fits
+    }
+
+    /// Rows that still have garbage in them
+    pub fn garbage_rows(&self) -> u32 {
+        self.grid
+            .iter()
+            .filter(|row| row.contains(&Cell::Garbage))
+            .count() as u32
+    }
+
+    /// Check if no cell on the board is filled (perfect clear)
+    pub fn is_empty(&self) -> bool {
+        self.grid
+            .iter()
+            .all(|row| row.iter().all(|cell| cell.is_empty()))
+    }
+
     /// Check if the game is over (blocks in buffer zone after lock)
     pub fn is_topped_out(&self) -> bool {
-        for row in HEIGHT..(HEIGHT + BUFFER_HEIGHT) {
+        for row in self.height..self.total_height() {
             if self.grid[row].iter().any(|&cell| !cell.is_empty()) {
                 return true;
             }
@@ -118,10 +227,10 @@ impl Board {
 
     /// Get visible grid as flat array for JS (top-to-bottom for SVG rendering)
     pub fn get_visible_grid(&self) -> Vec<u8> {
-        let mut result = Vec::with_capacity(WIDTH * HEIGHT);
-        // Output from top row (HEIGHT-1) to bottom row (0) for SVG coordinate system
-        for row in (0..HEIGHT).rev() {
-            for col in 0..WIDTH {
+        let mut result = Vec::with_capacity(self.width * self.height);
+        // Output from top row (height-1) to bottom row (0) for SVG coordinate system
+        for row in (0..self.height).rev() {
+            for col in 0..self.width {
                 result.push(self.grid[row][col] as u8);
             }
         }
@@ -136,8 +245,8 @@ mod tests {
     #[test]
     fn test_new_board_is_empty() {
         let board = Board::new();
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
+        for y in 0..DEFAULT_HEIGHT {
+            for x in 0..DEFAULT_WIDTH {
                 assert!(board.get(x as i32, y as i32).unwrap().is_empty());
             }
         }
@@ -147,16 +256,91 @@ mod tests {
     fn test_line_clear() {
         let mut board = Board::new();
         // Fill bottom row
-        for x in 0..WIDTH {
+        for x in 0..DEFAULT_WIDTH {
             board.set(x as i32, 0, Cell::I);
         }
         assert_eq!(board.clear_lines(), 1);
         // Bottom row should now be empty
-        for x in 0..WIDTH {
+        for x in 0..DEFAULT_WIDTH {
             assert!(board.get(x as i32, 0).unwrap().is_empty());
         }
     }
 
+    fn fill_rows_except(board: &mut Board, rows: std::ops::Range<i32>, holes: &[(i32, i32)]) {
+        for y in rows {
+            for x in 0..board.width() as i32 {
+                if !holes.contains(&(x, y)) {
+                    board.set(x, y, Cell::J);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tetris_perfect_clear() {
+        let mut board = Board::new();
+        let well = [(9, 0), (9, 1), (9, 2), (9, 3)];
+        fill_rows_except(&mut board, 0..4, &well);
+        assert!(!board.is_empty());
+
+        board.lock_cells(&well, Cell::I);
+        assert_eq!(board.clear_lines(), 4);
+        assert!(board.is_empty());
+    }
+
+    #[test]
+    fn test_perfect_clear_over_two_locks() {
+        let mut board = Board::new();
+        // Bottom two rows open on the right, top two open on the left
+        fill_rows_except(
+            &mut board,
+            0..4,
+            &[(8, 0), (9, 0), (8, 1), (9, 1), (0, 2), (1, 2), (0, 3), (1, 3)],
+        );
+
+        board.lock_cells(&[(8, 0), (9, 0), (8, 1), (9, 1)], Cell::O);
+        assert_eq!(board.clear_lines(), 2);
+        assert!(!board.is_empty());
+
+        board.lock_cells(&[(0, 0), (1, 0), (0, 1), (1, 1)], Cell::O);
+        assert_eq!(board.clear_lines(), 2);
+        assert!(board.is_empty());
+    }
+
+    #[test]
+    fn test_leftover_cell_is_not_perfect_clear() {
+        let mut board = Board::new();
+        let well = [(9, 0), (9, 1), (9, 2), (9, 3)];
+        fill_rows_except(&mut board, 0..4, &well);
+        board.set(0, 4, Cell::T);
+
+        board.lock_cells(&well, Cell::I);
+        assert_eq!(board.clear_lines(), 4);
+        assert!(!board.is_empty());
+    }
+
+    #[test]
+    fn test_custom_size_bounds() {
+        let mut board = Board::with_size(4, 8);
+        assert_eq!((board.width(), board.height()), (4, 8));
+        assert!(board.check_collision(&[(4, 0)]));
+        assert!(!board.check_collision(&[(3, 11)]));
+
+        fill_rows_except(&mut board, 0..1, &[]);
+        assert_eq!(board.clear_lines(), 1);
+        assert!(board.is_empty());
+
+        board.set(0, 8, Cell::T);
+        assert!(board.is_topped_out());
+    }
+
+    #[test]
+    fn test_size_is_clamped() {
+        let board = Board::with_size(1, 100);
+        assert_eq!((board.width(), board.height()), (MIN_WIDTH, MAX_HEIGHT));
+        assert_eq!(Board::with_size(20, 40).spawn_position(), (9, 40));
+    }
+
     #[test]
     fn test_collision_detection() {
         let mut board = Board::new();
@@ -167,4 +351,22 @@ mod tests {
         assert!(board.check_collision(&[(-1, 0)])); // Out of bounds
         assert!(board.check_collision(&[(0, -1)])); // Below board
     }
+
+    #[test]
+    fn test_insert_garbage_pushes_stack_up() {
+        let mut board = Board::new();
+        board.set(4, 0, Cell::T);
+        assert!(board.insert_garbage(2, 3));
+        assert_eq!(board.get(4, 2), Some(Cell::T));
+        for y in 0..2 {
+            assert_eq!(board.get(3, y), Some(Cell::Empty));
+            assert_eq!(board.get(4, y), Some(Cell::Garbage));
+        }
+        assert_eq!(board.garbage_rows(), 2);
+
+        // Garbage pushing a block off the top of the buffer tops out
+        let top = (DEFAULT_HEIGHT + BUFFER_HEIGHT - 1) as i32;
+        board.set(0, top, Cell::I);
+        assert!(!board.insert_garbage(1, 0));
+    }
 }
@@ -0,0 +1,127 @@
+use crate::piece::{PieceType, Rotation};
+use crate::scoring::ClearKind;
+use serde::{Deserialize, Serialize};
+
+/// Why a game ended
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum GameOverReason {
+    /// The next piece overlapped the stack where it spawns
+    BlockOut = 0,
+    /// A piece locked with blocks above the visible board
+    LockOut = 1,
+    /// Rising garbage pushed the stack off the top of the board
+    TopOut = 2,
+}
+
+/// Something that happened in the engine, queued for the frontend to react to
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GameEvent {
+    PieceSpawned {
+        piece: PieceType,
+    },
+    /// Position and rotation the piece locked at
+    PieceLocked {
+        piece: PieceType,
+        x: i32,
+        y: i32,
+        rotation: Rotation,
+    },
+    /// Board rows (0 = bottom) that were full, in the classification `kind`
+    LinesCleared {
+        rows: Vec<u32>,
+        kind: ClearKind,
+    },
+    LevelUp {
+        level: u32,
+    },
+    /// `piece` was put into hold
+    HoldUsed {
+        piece: PieceType,
+    },
+    GameOver {
+        reason: GameOverReason,
+    },
+}
+
+impl GameEvent {
+    /// Event type code used by `encode`
+    pub fn code(&self) -> i32 {
+        match self {
+            GameEvent::PieceSpawned { .. } => 0,
+            GameEvent::PieceLocked { .. } => 1,
+            GameEvent::LinesCleared { .. } => 2,
+            GameEvent::LevelUp { .. } => 3,
+            GameEvent::HoldUsed { .. } => 4,
+            GameEvent::GameOver { .. } => 5,
+        }
+    }
+
+    /// Append the event as `[code, payload length, payload...]`. Pieces are
+    /// their cell colors (1-7). Payloads:
+    /// - PieceSpawned: piece
+    /// - PieceLocked: piece, x, y, rotation (0-3)
+    /// - LinesCleared: kind (`ClearKind` value), then each row
+    /// - LevelUp: level
+    /// - HoldUsed: piece
+    /// - GameOver: reason (0 = block out, 1 = lock out, 2 = top out)
+    pub fn encode(&self, out: &mut Vec<i32>) {
+        let piece_code = |piece: &PieceType| piece.to_cell() as i32;
+        let payload: Vec<i32> = match self {
+            GameEvent::PieceSpawned { piece } | GameEvent::HoldUsed { piece } => {
+                vec![piece_code(piece)]
+            }
+            GameEvent::PieceLocked {
+                piece,
+                x,
+                y,
+                rotation,
+            } => vec![piece_code(piece), *x, *y, *rotation as i32],
+            GameEvent::LinesCleared { rows, kind } => std::iter::once(*kind as i32)
+                .chain(rows.iter().map(|&row| row as i32))
+                .collect(),
+            GameEvent::LevelUp { level } => vec![*level as i32],
+            GameEvent::GameOver { reason } => vec![*reason as i32],
+        };
+        out.push(self.code());
+        out.push(payload.len() as i32);
+        out.extend(payload);
+    }
+}
+
+/// Flatten events for transfer to JS (see `GameEvent::encode`)
+pub fn encode_events(events: &[GameEvent]) -> Vec<i32> {
+    let mut out = Vec::new();
+    for event in events {
+        event.encode(&mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_is_length_prefixed() {
+        let events = [
+            GameEvent::PieceLocked {
+                piece: PieceType::T,
+                x: 4,
+                y: -1,
+                rotation: Rotation::R180,
+            },
+            GameEvent::LinesCleared {
+                rows: vec![0, 1],
+                kind: ClearKind::TSpinDouble,
+            },
+            GameEvent::GameOver {
+                reason: GameOverReason::LockOut,
+            },
+        ];
+        assert_eq!(
+            encode_events(&events),
+            vec![1, 4, 3, 4, -1, 2, 2, 3, 10, 0, 1, 5, 1, 1]
+        );
+    }
+}
@@ -1,8 +1,15 @@
-use crate::board::Board;
-use crate::piece::{Piece, PieceType};
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use crate::board::{Board, BUFFER_HEIGHT, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+use crate::events::{GameEvent, GameOverReason};
+use crate::gravity::{drop_interval_ms, GravityCurve, GravityKind, MAX_GRAVITY};
+use crate::mode::{master_level, GameMode};
+use crate::piece::{Piece, PieceType, Turn};
+use crate::randomizer::{PieceRandomizer, Randomizer};
+use crate::rng::Rng;
+use crate::rotation::RotationKind;
+use crate::scoring::{ClearKind, LockScore, ScoringKind, ScoringRules, SpinKind};
+use crate::stats::Statistics;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum MoveResult {
@@ -12,18 +19,129 @@ pub enum MoveResult {
     GameOver,
 }
 
+/// Most pieces the next queue can show
+pub const MAX_PREVIEWS: usize = 6;
+/// Next queue length for new games
+pub const DEFAULT_PREVIEWS: usize = 5;
+/// Highest level a game can start at
+pub const MAX_START_LEVEL: u32 = 30;
+
+/// Mixed into the seed for garbage holes, so they don't disturb the piece sequence
+const GARBAGE_SEED: u64 = 0x6761_7262_6167_6521;
+
+/// Index of the last kick test of a 90° SRS turn, which upgrades a T-spin mini
+/// to a full T-spin
+const TST_KICK_INDEX: usize = 4;
+
+/// Inputs held while a piece spawns, applied to it before its first gravity step
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpawnInput {
+    /// Initial rotation (IRS)
+    pub rotation: Option<Turn>,
+    /// Initial hold (IHS)
+    pub hold: bool,
+}
+
+/// Board as it was before a line clear, kept while the clear is shown
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LineClear {
+    pub board: Board,
+    /// Cleared rows (0 = bottom)
+    pub rows: Vec<u32>,
+}
+
+/// Settings fixed when a game is created
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameConfig {
+    /// Piece sequence seed; `None` picks a fresh random seed
+    pub seed: Option<u64>,
+    pub randomizer: PieceRandomizer,
+    /// Visible next queue length (0 to `MAX_PREVIEWS`)
+    pub preview_count: usize,
+    /// Visible board size in cells, clamped to the range `Board` supports
+    pub width: usize,
+    pub height: usize,
+    pub rotation: RotationKind,
+    pub scoring: ScoringKind,
+    pub gravity: GravityKind,
+    /// Level the game starts at (1 or more)
+    pub start_level: u32,
+    pub mode: GameMode,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            randomizer: PieceRandomizer::default(),
+            preview_count: DEFAULT_PREVIEWS,
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            rotation: RotationKind::default(),
+            scoring: ScoringKind::default(),
+            gravity: GravityKind::default(),
+            start_level: 1,
+            mode: GameMode::default(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Game {
     pub board: Board,
     pub current_piece: Option<Piece>,
-    pub next_piece: PieceType,
     pub hold_piece: Option<PieceType>,
     pub can_hold: bool,
     pub score: u32,
     pub level: u32,
     pub lines_cleared: u32,
+    /// Pieces locked so far
+    pub pieces: u32,
     pub game_over: bool,
-    bag: Vec<PieceType>,
+    /// Classification of the most recent lock
+    pub last_clear: ClearKind,
+    /// Consecutive locks that cleared lines (0 after a lock without a clear)
+    pub combo: u32,
+    /// Whether the last line clear was a Tetris or T-spin, so the next one earns the B2B bonus
+    pub back_to_back: bool,
+    /// Seed of the piece generator, enough to reproduce the piece sequence
+    pub seed: u64,
+    /// IRS/IHS input for the next spawn, consumed by `spawn_piece`
+    pub spawn_input: SpawnInput,
+    /// Spawn the next piece as soon as one locks. The controller turns this
+    /// off to run entry and line-clear delays itself.
+    pub spawn_on_lock: bool,
+    /// Last line clear while spawning is left to the caller, until the next spawn
+    pub line_clear: Option<LineClear>,
+    /// Lock and clear totals (absent from snapshots saved before them)
+    #[serde(default)]
+    pub stats: Statistics,
+    rng: Rng,
+    // Picks garbage hole columns
+    garbage_rng: Rng,
+    // Hole column of the last garbage row, so the next one moves
+    garbage_hole: Option<usize>,
+    randomizer: PieceRandomizer,
+    rotation: RotationKind,
+    scoring: ScoringKind,
+    gravity: GravityKind,
+    start_level: u32,
+    mode: GameMode,
+    // Lines cleared by the current combo
+    combo_lines: u32,
+    // Rows the current piece was soft and hard dropped, for rules that score them at lock
+    soft_drop_rows: u32,
+    hard_drop_rows: u32,
+    // Upcoming pieces, always MAX_PREVIEWS long so the sequence doesn't depend on the preview count
+    queue: VecDeque<PieceType>,
+    // Number of queued pieces the player may see
+    preview_count: usize,
+    // Set when a lock empties the board, until the UI takes it
+    perfect_clear: bool,
+    // Turn and kick used by the last successful rotation, cleared by any later movement
+    last_kick: Option<(Turn, usize)>,
+    // Events not yet taken by `drain_events`
+    events: Vec<GameEvent>,
 }
 
 impl Default for Game {
@@ -34,52 +152,174 @@ impl Default for Game {
 
 impl Game {
     pub fn new() -> Self {
+        Self::with_config(GameConfig::default())
+    }
+
+    /// Create a game whose piece sequence is fully determined by `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_config(GameConfig {
+            seed: Some(seed),
+            ..GameConfig::default()
+        })
+    }
+
+    pub fn with_config(mut config: GameConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(Rng::random_seed);
+        // Master always runs at 20G under TGM scoring, counting levels from 0
+        if config.mode == GameMode::Master {
+            config.gravity = GravityKind::Table(vec![MAX_GRAVITY]);
+            config.scoring = ScoringKind::Tgm;
+            config.start_level = 0;
+        } else {
+            config.start_level = config.start_level.max(1);
+        }
         let mut game = Self {
-            board: Board::new(),
+            board: Board::with_size(config.width, config.height),
             current_piece: None,
-            next_piece: PieceType::T, // Will be replaced
             hold_piece: None,
             can_hold: true,
             score: 0,
-            level: 1,
+            level: config.start_level,
             lines_cleared: 0,
+            pieces: 0,
             game_over: false,
-            bag: Vec::new(),
+            last_clear: ClearKind::None,
+            combo: 0,
+            back_to_back: false,
+            seed,
+            spawn_input: SpawnInput::default(),
+            spawn_on_lock: true,
+            line_clear: None,
+            stats: Statistics::default(),
+            rng: Rng::new(seed),
+            garbage_rng: Rng::new(seed ^ GARBAGE_SEED),
+            garbage_hole: None,
+            randomizer: config.randomizer,
+            rotation: config.rotation,
+            scoring: config.scoring,
+            gravity: config.gravity,
+            start_level: config.start_level,
+            mode: config.mode,
+            combo_lines: 0,
+            soft_drop_rows: 0,
+            hard_drop_rows: 0,
+            queue: VecDeque::with_capacity(MAX_PREVIEWS),
+            preview_count: config.preview_count.min(MAX_PREVIEWS),
+            perfect_clear: false,
+            last_kick: None,
+            events: Vec::new(),
         };
-        game.refill_bag();
-        game.next_piece = game.draw_from_bag();
+        for _ in 0..MAX_PREVIEWS {
+            let piece = game.draw_piece();
+            game.queue.push_back(piece);
+        }
+        let garbage = game
+            .mode
+            .garbage_rows()
+            .min(game.board.height().saturating_sub(2) as u32);
+        game.add_garbage(garbage);
         game.spawn_piece();
         game
     }
 
-    fn refill_bag(&mut self) {
-        let mut pieces = PieceType::all().to_vec();
-        pieces.shuffle(&mut thread_rng());
-        self.bag = pieces;
+    fn draw_piece(&mut self) -> PieceType {
+        self.randomizer.next_piece(&mut self.rng)
     }
 
-    fn draw_from_bag(&mut self) -> PieceType {
-        if self.bag.is_empty() {
-            self.refill_bag();
-        }
-        self.bag.pop().unwrap()
+    /// The piece that spawns next, whether or not it is previewed
+    pub fn next_piece(&self) -> PieceType {
+        self.queue[0]
+    }
+
+    /// Up to `n` upcoming pieces, limited to the visible preview count
+    pub fn peek_queue(&self, n: usize) -> Vec<PieceType> {
+        self.queue
+            .iter()
+            .take(n.min(self.preview_count))
+            .copied()
+            .collect()
+    }
+
+    pub fn preview_count(&self) -> usize {
+        self.preview_count
     }
 
+    /// Rotation system the game was created with
+    pub fn rotation_system(&self) -> RotationKind {
+        self.rotation
+    }
+
+    /// Scoring ruleset the game was created with
+    pub fn scoring(&self) -> ScoringKind {
+        self.scoring
+    }
+
+    /// Set how many queued pieces are visible (0 to MAX_PREVIEWS)
+    pub fn set_preview_count(&mut self, count: usize) {
+        self.preview_count = count.min(MAX_PREVIEWS);
+    }
+
+    /// Whether a deserialized game holds together: a full queue, at most
+    /// MAX_PREVIEWS of it visible and the falling piece clear of the stack
+    /// and walls. The board's own size is checked as it's deserialized.
+    pub fn is_consistent(&self) -> bool {
+        self.queue.len() == MAX_PREVIEWS
+            && self.preview_count <= MAX_PREVIEWS
+            && self
+                .current_piece
+                .as_ref()
+                .is_none_or(|piece| !self.board.check_collision(&piece.get_blocks()))
+    }
+
+    fn pop_queue(&mut self) -> PieceType {
+        let piece_type = self.queue.pop_front().unwrap();
+        let refill = self.draw_piece();
+        self.queue.push_back(refill);
+        piece_type
+    }
+
+    /// Spawn the next piece, applying and clearing `spawn_input`
     pub fn spawn_piece(&mut self) -> bool {
-        let piece_type = self.next_piece;
-        self.next_piece = self.draw_from_bag();
-        let piece = Piece::new(piece_type);
+        let input = std::mem::take(&mut self.spawn_input);
+        self.spawn_with(input)
+    }
+
+    fn spawn_with(&mut self, input: SpawnInput) -> bool {
+        self.line_clear = None;
+        let mut piece_type = self.pop_queue();
+        self.can_hold = true;
+
+        // IHS: the spawning piece goes straight to hold
+        if input.hold {
+            self.events.push(GameEvent::HoldUsed { piece: piece_type });
+            piece_type = match self.hold_piece.replace(piece_type) {
+                Some(held) => held,
+                None => self.pop_queue(),
+            };
+            self.can_hold = false;
+        }
+        let mut piece = Piece::spawn(piece_type, &self.board, self.rotation);
+
+        // IRS: spawn already rotated if that fits, without kicks
+        if let Some(turn) = input.rotation {
+            let rotated = piece.get_blocks_after_rotation(turn, (0, 0));
+            if !self.board.check_collision(&rotated) {
+                piece.rotate(turn);
+            }
+        }
 
         // Check if spawn position is valid
         let blocks = piece.get_blocks();
         if self.board.check_collision(&blocks) {
-            self.game_over = true;
+            self.end(GameOverReason::BlockOut);
             self.current_piece = None;
             return false;
         }
 
         self.current_piece = Some(piece);
-        self.can_hold = true;
+        self.last_kick = None;
+        self.events
+            .push(GameEvent::PieceSpawned { piece: piece_type });
         true
     }
 
@@ -100,11 +340,12 @@ impl Game {
             let piece = self.current_piece.as_mut().unwrap();
             piece.x += dx;
             piece.y += dy;
+            self.last_kick = None;
             MoveResult::Success
         }
     }
 
-    pub fn rotate(&mut self, clockwise: bool) -> MoveResult {
+    pub fn rotate(&mut self, turn: Turn) -> MoveResult {
         if self.game_over {
             return MoveResult::GameOver;
         }
@@ -118,15 +359,16 @@ impl Game {
             return MoveResult::Success;
         }
 
-        let kicks = piece.get_kicks(clockwise);
+        let kicks = piece.get_kicks(&self.board, turn);
 
-        for &kick in kicks.iter() {
-            let new_blocks = piece.get_blocks_after_rotation(clockwise, kick);
+        for (index, &kick) in kicks.iter().enumerate() {
+            let new_blocks = piece.get_blocks_after_rotation(turn, kick);
             if !self.board.check_collision(&new_blocks) {
                 let piece = self.current_piece.as_mut().unwrap();
-                piece.rotate(clockwise);
+                piece.rotate(turn);
                 piece.x += kick.0;
                 piece.y += kick.1;
+                self.last_kick = Some((turn, index));
                 return MoveResult::Success;
             }
         }
@@ -137,7 +379,8 @@ impl Game {
     pub fn soft_drop(&mut self) -> MoveResult {
         let result = self.move_piece(0, -1);
         if result == MoveResult::Success {
-            self.score += 1; // Soft drop bonus
+            self.score += self.scoring.soft_drop(self.level);
+            self.soft_drop_rows += 1;
         }
         result
     }
@@ -147,15 +390,23 @@ impl Game {
             return MoveResult::GameOver;
         }
 
-        let mut drop_distance = 0;
-        while self.move_piece(0, -1) == MoveResult::Success {
-            drop_distance += 1;
-        }
-        self.score += drop_distance * 2; // Hard drop bonus
+        let drop_distance = self.drop_to_floor();
+        self.score += self.scoring.hard_drop(drop_distance);
+        self.hard_drop_rows = drop_distance;
 
         self.lock_piece()
     }
 
+    /// Move the piece down as far as it goes without locking (20G).
+    /// Returns the rows it fell.
+    pub fn drop_to_floor(&mut self) -> u32 {
+        let mut rows = 0;
+        while self.move_piece(0, -1) == MoveResult::Success {
+            rows += 1;
+        }
+        rows
+    }
+
     pub fn tick(&mut self) -> MoveResult {
         if self.game_over {
             return MoveResult::GameOver;
@@ -168,39 +419,78 @@ impl Game {
             return MoveResult::Success;
         }
 
-        let move_result = self.move_piece(0, -1);
+        // A grounded piece stays put; locking is left to the controller's lock delay
+        self.move_piece(0, -1)
+    }
 
-        if move_result == MoveResult::Failed {
-            self.lock_piece()
-        } else {
-            move_result
+    /// Check if the current piece is resting on the stack or floor
+    pub fn is_grounded(&self) -> bool {
+        match &self.current_piece {
+            Some(piece) => self.board.check_collision(&piece.get_blocks_after_move(0, -1)),
+            None => false,
         }
     }
 
-    fn lock_piece(&mut self) -> MoveResult {
+    /// Lock the current piece in place, clear lines and spawn the next piece
+    /// (unless `spawn_on_lock` is off)
+    pub fn lock_piece(&mut self) -> MoveResult {
         let Some(piece) = self.current_piece.take() else {
             return MoveResult::Failed;
         };
 
+        let spin = self.detect_t_spin(&piece);
         let blocks = piece.get_blocks();
         let cell = piece.piece_type.to_cell();
         self.board.lock_cells(&blocks, cell);
+        self.pieces += 1;
+        self.events.push(GameEvent::PieceLocked {
+            piece: piece.piece_type,
+            x: piece.x,
+            y: piece.y,
+            rotation: piece.rotation,
+        });
 
         // Clear lines and score
+        let rows = self.board.full_rows();
+        if !self.spawn_on_lock && !rows.is_empty() {
+            self.line_clear = Some(LineClear {
+                board: self.board.clone(),
+                rows: rows.clone(),
+            });
+        }
         let lines = self.board.clear_lines();
+        let clear = ClearKind::classify(lines, spin);
+        self.last_clear = clear;
+        let perfect_clear = lines > 0 && self.board.is_empty();
+        self.score += self.lock_points(clear, perfect_clear);
+        self.perfect_clear |= perfect_clear;
+        self.soft_drop_rows = 0;
+        self.hard_drop_rows = 0;
         if lines > 0 {
+            self.events
+                .push(GameEvent::LinesCleared { rows, kind: clear });
+            self.combo += 1;
+            self.combo_lines += lines;
+            self.back_to_back = clear.is_difficult();
             self.lines_cleared += lines;
-            self.score += self.calculate_line_score(lines);
-            self.update_level();
+        } else {
+            self.combo = 0;
+            self.combo_lines = 0;
         }
+        self.stats
+            .record_lock(piece.piece_type, clear, lines, self.combo);
+        self.update_level(lines);
 
         // Check game over
         if self.board.is_topped_out() {
-            self.game_over = true;
+            self.end(GameOverReason::LockOut);
             return MoveResult::GameOver;
         }
 
         // Spawn next piece
+        if !self.spawn_on_lock {
+            return MoveResult::Locked;
+        }
         if !self.spawn_piece() {
             return MoveResult::GameOver;
         }
@@ -208,20 +498,120 @@ impl Game {
         MoveResult::Locked
     }
 
-    fn calculate_line_score(&self, lines: u32) -> u32 {
-        let base = match lines {
-            1 => 100,
-            2 => 300,
-            3 => 500,
-            4 => 800, // Tetris!
-            _ => 0,
+    /// Score a lock under the game's ruleset.
+    /// Must be called before `combo` and `back_to_back` are updated for this lock.
+    fn lock_points(&self, clear: ClearKind, perfect_clear: bool) -> u32 {
+        self.scoring.lock(&LockScore {
+            clear,
+            level: self.level,
+            combo: self.combo,
+            combo_lines: self.combo_lines,
+            back_to_back: self.back_to_back,
+            perfect_clear,
+            soft_drop_rows: self.soft_drop_rows,
+            hard_drop_rows: self.hard_drop_rows,
+        })
+    }
+
+    /// Check a piece about to lock for a T-spin using the 3-corner rule
+    fn detect_t_spin(&self, piece: &Piece) -> SpinKind {
+        if piece.piece_type != PieceType::T {
+            return SpinKind::None;
+        }
+        let Some((turn, kick)) = self.last_kick else {
+            return SpinKind::None;
+        };
+
+        // The center block touches the other three; the stem is the one without an opposite
+        let blocks = piece.get_blocks();
+        let touches =
+            |(ax, ay): (i32, i32), (bx, by): (i32, i32)| (ax - bx).abs() + (ay - by).abs() == 1;
+        let Some(&(cx, cy)) = blocks
+            .iter()
+            .find(|&&center| blocks.iter().filter(|&&b| touches(center, b)).count() == 3)
+        else {
+            return SpinKind::None;
+        };
+        let Some((sx, sy)) = blocks
+            .iter()
+            .map(|&(x, y)| (x - cx, y - cy))
+            .find(|&(dx, dy)| (dx, dy) != (0, 0) && !blocks.contains(&(cx - dx, cy - dy)))
+        else {
+            return SpinKind::None;
+        };
+
+        // Corners around the T center, on the stem side and behind it; walls and floor count as filled
+        let filled = |dx: i32, dy: i32| !self.board.is_valid_position(cx + dx, cy + dy);
+        let front = [(sx + sy, sy + sx), (sx - sy, sy - sx)];
+        let back = [(sy - sx, sx - sy), (-sx - sy, -sy - sx)];
+        let front_filled = front.iter().filter(|&&(dx, dy)| filled(dx, dy)).count();
+        let back_filled = back.iter().filter(|&&(dx, dy)| filled(dx, dy)).count();
+
+        // Only the 5-test SRS tables have the upgrading kick; the 180 table has more tests
+        let tst_kick = kick == TST_KICK_INDEX
+            && turn != Turn::Half
+            && matches!(self.rotation, RotationKind::Srs | RotationKind::SrsPlus);
+        if front_filled + back_filled < 3 {
+            SpinKind::None
+        } else if front_filled == 2 || tst_kick {
+            SpinKind::Full
+        } else {
+            SpinKind::Mini
+        }
+    }
+
+    /// Push `rows` messy garbage rows up under the stack, each with its hole in a
+    /// different column from the row below. The falling piece moves up with the
+    /// stack if it would overlap. Returns false if the stack was pushed off the top.
+    pub fn add_garbage(&mut self, rows: u32) -> bool {
+        if self.game_over {
+            return false;
+        }
+        let width = self.board.width() as u32;
+        for _ in 0..rows {
+            let mut hole = self.garbage_rng.below(width - 1) as usize;
+            if self.garbage_hole.is_some_and(|last| hole >= last) {
+                hole += 1;
+            }
+            self.garbage_hole = Some(hole);
+            if !self.board.insert_garbage(1, hole) {
+                self.end(GameOverReason::TopOut);
+                self.current_piece = None;
+                return false;
+            }
+        }
+        if let Some(piece) = &mut self.current_piece {
+            while self.board.check_collision(&piece.get_blocks()) {
+                piece.y += 1;
+            }
+        }
+        true
+    }
+
+    /// Report whether a perfect clear happened since the last call
+    pub fn take_perfect_clear(&mut self) -> bool {
+        std::mem::take(&mut self.perfect_clear)
+    }
+
+    fn update_level(&mut self, lines: u32) {
+        let level = match self.mode {
+            GameMode::Master => master_level(self.level, lines),
+            _ => self.gravity.level(self.start_level, self.lines_cleared),
         };
-        base * self.level
+        if level > self.level {
+            self.events.push(GameEvent::LevelUp { level });
+        }
+        self.level = level;
+    }
+
+    fn end(&mut self, reason: GameOverReason) {
+        self.game_over = true;
+        self.events.push(GameEvent::GameOver { reason });
     }
 
-    fn update_level(&mut self) {
-        // Level up every 10 lines
-        self.level = (self.lines_cleared / 10) + 1;
+    /// Take the events queued since the last call, oldest first
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
     }
 
     pub fn hold(&mut self) -> MoveResult {
@@ -234,18 +624,24 @@ impl Game {
         };
 
         let current_type = current.piece_type;
+        self.events.push(GameEvent::HoldUsed {
+            piece: current_type,
+        });
 
         if let Some(held) = self.hold_piece {
             // Swap with held piece
             self.hold_piece = Some(current_type);
-            self.current_piece = Some(Piece::new(held));
+            self.current_piece = Some(Piece::spawn(held, &self.board, self.rotation));
+            self.last_kick = None;
+            self.events.push(GameEvent::PieceSpawned { piece: held });
         } else {
             // First hold
             self.hold_piece = Some(current_type);
-            self.spawn_piece();
+            self.spawn_with(SpawnInput::default());
         }
 
         self.can_hold = false;
+        self.soft_drop_rows = 0;
         MoveResult::Success
     }
 
@@ -270,37 +666,35 @@ impl Game {
         Some(ghost_y)
     }
 
-    /// Get drop speed in milliseconds based on level (NES-style curve)
-    pub fn get_drop_interval(&self) -> u32 {
-        // NES Tetris-inspired speed curve (frames at 60fps, converted to ms)
-        let frames = match self.level {
-            1 => 48,
-            2 => 43,
-            3 => 38,
-            4 => 33,
-            5 => 28,
-            6 => 23,
-            7 => 18,
-            8 => 13,
-            9 => 8,
-            10..=12 => 6,
-            13..=15 => 5,
-            16..=18 => 4,
-            19..=28 => 3,
-            _ => 2, // Level 29+
-        };
-        // Convert frames to ms (assuming 60fps base)
-        (frames * 1000) / 60
+    /// Goal the game is played for
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    /// Level the game started at
+    pub fn start_level(&self) -> u32 {
+        self.start_level
     }
 
-    /// Get acceleration factor based on piece height (subtle: 1.0 to 1.15)
-    pub fn get_height_acceleration(&self) -> f32 {
+    /// Falling speed at the current level, in G (rows per frame at 60 fps)
+    pub fn gravity(&self) -> f64 {
+        self.gravity.gravity(self.level)
+    }
+
+    /// Time per row at the current level in ms, below one frame past 1G
+    pub fn get_drop_interval(&self) -> f64 {
+        drop_interval_ms(self.gravity())
+    }
+
+    /// Get acceleration in percent based on piece height (subtle: 100 to 115)
+    pub fn get_height_acceleration(&self) -> u32 {
         if let Some(piece) = &self.current_piece {
-            // Lower pieces fall slightly faster
-            let height_ratio = 1.0 - (piece.y as f32 / 24.0);
-            1.0 + (height_ratio * 0.15) // Max 15% faster at bottom
+            // Lower pieces fall slightly faster, in whole percent so every machine agrees
+            let rows = (self.board.height() + BUFFER_HEIGHT) as i32;
+            let depth = (rows - piece.y).clamp(0, rows) as u32;
+            100 + depth * 15 / rows as u32 // Max 15% faster at bottom
         } else {
-            1.0
+            100
         }
     }
 }
@@ -308,6 +702,9 @@ impl Game {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board::{Cell, DEFAULT_WIDTH};
+    use crate::piece::Rotation;
+    use crate::randomizer::ScriptedRandomizer;
 
     #[test]
     fn test_new_game() {
@@ -318,6 +715,213 @@ mod tests {
         assert_eq!(game.level, 1);
     }
 
+    /// Current piece followed by the next 99 spawns, as piece letters
+    fn piece_sequence(game: &mut Game) -> String {
+        let mut sequence = String::new();
+        for _ in 0..100 {
+            let piece = game.current_piece.as_ref().unwrap().piece_type;
+            sequence.push_str(&format!("{:?}", piece));
+            game.spawn_piece();
+        }
+        sequence
+    }
+
+    #[test]
+    fn test_seeded_sequence_is_pinned() {
+        let mut game = Game::with_seed(20240101);
+        assert_eq!(
+            piece_sequence(&mut game),
+            "ILJZTOSJZTOLISZSOLJITZTLJOSITOSILJZOSZILTJTLJOIZSLOJZSTILJSTOIZSZJTIOLOLZTISJOLITZSJOJISLZTZITOJLSJZ"
+        );
+    }
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = Game::with_seed(99);
+        let mut b = Game::with_seed(99);
+        let mut c = Game::with_seed(100);
+        let sequence = piece_sequence(&mut a);
+        assert_eq!(sequence, piece_sequence(&mut b));
+        assert_ne!(sequence, piece_sequence(&mut c));
+    }
+
+    #[test]
+    fn test_seeded_bags_hold_all_pieces() {
+        let mut game = Game::with_seed(5);
+        let sequence = piece_sequence(&mut game);
+        for bag in sequence.as_bytes().chunks(7).filter(|bag| bag.len() == 7) {
+            let mut bag = bag.to_vec();
+            bag.sort();
+            assert_eq!(bag, b"IJLOSTZ");
+        }
+    }
+
+    #[test]
+    fn test_scripted_randomizer_feeds_next_piece() {
+        let script = ScriptedRandomizer::parse("TIO").unwrap();
+        let mut game = Game::with_config(GameConfig {
+            randomizer: PieceRandomizer::Scripted(script),
+            ..GameConfig::default()
+        });
+        assert_eq!(piece_sequence(&mut game)[..8], *"TIOTIOTI");
+    }
+
+    #[test]
+    fn test_peek_queue_follows_spawns() {
+        let mut game = Game::with_seed(3);
+        let queue = game.peek_queue(MAX_PREVIEWS);
+        assert_eq!(queue.len(), DEFAULT_PREVIEWS);
+        assert_eq!(queue[0], game.next_piece());
+
+        game.spawn_piece();
+        assert_eq!(game.current_piece.as_ref().unwrap().piece_type, queue[0]);
+        assert_eq!(game.peek_queue(2), queue[1..3]);
+    }
+
+    #[test]
+    fn test_preview_count_limits_peek() {
+        let mut game = Game::with_seed(3);
+        game.set_preview_count(0);
+        assert!(game.peek_queue(3).is_empty());
+        game.set_preview_count(10);
+        assert_eq!(game.preview_count(), MAX_PREVIEWS);
+        assert_eq!(game.peek_queue(10).len(), MAX_PREVIEWS);
+    }
+
+    #[test]
+    fn test_height_acceleration_follows_board_size() {
+        for height in [4, 20, 40] {
+            let mut game = Game::with_config(GameConfig {
+                height,
+                ..GameConfig::default()
+            });
+            let rows = (height + BUFFER_HEIGHT) as i32;
+            game.current_piece.as_mut().unwrap().y = rows;
+            assert_eq!(game.get_height_acceleration(), 100);
+            game.current_piece.as_mut().unwrap().y = 0;
+            assert_eq!(game.get_height_acceleration(), 115);
+            game.current_piece.as_mut().unwrap().y = rows / 2;
+            assert_eq!(game.get_height_acceleration(), 107);
+        }
+    }
+
+    #[test]
+    fn test_spawn_follows_board_size() {
+        for (width, height) in [(4, 10), (20, 40)] {
+            let mut game = Game::with_config(GameConfig {
+                width,
+                height,
+                ..GameConfig::default()
+            });
+            for _ in 0..14 {
+                let piece = game.current_piece.as_ref().unwrap();
+                assert_eq!(piece.y, height as i32);
+                assert!(!game.board.check_collision(&piece.get_blocks()));
+                game.spawn_piece();
+            }
+        }
+    }
+
+    #[test]
+    fn test_initial_rotation() {
+        let mut game = Game::with_config(GameConfig {
+            randomizer: PieceRandomizer::Scripted(ScriptedRandomizer::parse("T").unwrap()),
+            ..GameConfig::default()
+        });
+        game.spawn_input.rotation = Some(Turn::Clockwise);
+        game.hard_drop();
+        assert_eq!(game.current_piece.as_ref().unwrap().rotation, Rotation::R90);
+        assert_eq!(game.spawn_input, SpawnInput::default());
+
+        // Blocked at spawn, but the pre-rotated T fits
+        let (x, y) = game.board.spawn_position();
+        game.board.set(x - 1, y, Cell::O);
+        game.spawn_input.rotation = Some(Turn::Clockwise);
+        assert!(game.spawn_piece());
+        assert_eq!(game.current_piece.as_ref().unwrap().rotation, Rotation::R90);
+        assert!(!game.spawn_piece());
+    }
+
+    #[test]
+    fn test_initial_hold() {
+        let mut game = Game::with_config(GameConfig {
+            randomizer: PieceRandomizer::Scripted(ScriptedRandomizer::parse("IOTS").unwrap()),
+            ..GameConfig::default()
+        });
+        game.spawn_input.hold = true;
+        game.hard_drop();
+        // O went to hold as it spawned, T took its place
+        assert_eq!(game.hold_piece, Some(PieceType::O));
+        let current = |game: &Game| game.current_piece.as_ref().unwrap().piece_type;
+        assert_eq!(current(&game), PieceType::T);
+        assert!(!game.can_hold);
+
+        game.spawn_input.hold = true;
+        game.hard_drop();
+        assert_eq!(game.hold_piece, Some(PieceType::S));
+        assert_eq!(current(&game), PieceType::O);
+    }
+
+    #[test]
+    fn test_events_follow_a_line_clear() {
+        let mut game = Game::with_config(GameConfig {
+            randomizer: PieceRandomizer::Scripted(ScriptedRandomizer::parse("IT").unwrap()),
+            ..GameConfig::default()
+        });
+        assert_eq!(
+            game.drain_events(),
+            vec![GameEvent::PieceSpawned {
+                piece: PieceType::I
+            }]
+        );
+
+        game.lines_cleared = 9;
+        fill_row(&mut game, 0, &[3, 4, 5, 6]);
+        game.hold();
+        drop_i(&mut game, 4);
+        assert_eq!(
+            game.drain_events(),
+            vec![
+                GameEvent::HoldUsed {
+                    piece: PieceType::I
+                },
+                GameEvent::PieceSpawned {
+                    piece: PieceType::T
+                },
+                GameEvent::PieceLocked {
+                    piece: PieceType::I,
+                    x: 4,
+                    y: 0,
+                    rotation: Rotation::R0,
+                },
+                GameEvent::LinesCleared {
+                    rows: vec![0],
+                    kind: ClearKind::Single,
+                },
+                GameEvent::LevelUp { level: 2 },
+                GameEvent::PieceSpawned {
+                    piece: PieceType::I
+                },
+            ]
+        );
+        assert!(game.drain_events().is_empty());
+    }
+
+    #[test]
+    fn test_block_out_event() {
+        let mut game = Game::new();
+        let (x, y) = game.board.spawn_position();
+        game.board.set(x, y, Cell::O);
+        game.drain_events();
+        assert!(!game.spawn_piece());
+        assert_eq!(
+            game.drain_events(),
+            vec![GameEvent::GameOver {
+                reason: GameOverReason::BlockOut
+            }]
+        );
+    }
+
     #[test]
     fn test_move_piece() {
         let mut game = Game::new();
@@ -333,7 +937,237 @@ mod tests {
     #[test]
     fn test_line_clear_scoring() {
         let game = Game::new();
-        assert_eq!(game.calculate_line_score(1), 100);
-        assert_eq!(game.calculate_line_score(4), 800);
+        assert_eq!(game.lock_points(ClearKind::Single, false), 100);
+        assert_eq!(game.lock_points(ClearKind::Tetris, false), 800);
+    }
+
+    #[test]
+    fn test_back_to_back_bonus() {
+        let mut game = Game::new();
+        game.back_to_back = true;
+        assert_eq!(game.lock_points(ClearKind::Tetris, false), 1200);
+        assert_eq!(game.lock_points(ClearKind::TSpinDouble, false), 1800);
+        assert_eq!(game.lock_points(ClearKind::Double, false), 300);
+    }
+
+    #[test]
+    fn test_combo_bonus() {
+        let mut game = Game::new();
+        game.combo = 2;
+        assert_eq!(game.lock_points(ClearKind::Single, false), 200);
+        assert_eq!(game.lock_points(ClearKind::None, false), 0);
+    }
+
+    /// Lock an I piece flat on the floor at the given column
+    fn drop_i(game: &mut Game, x: i32) {
+        let mut piece = Piece::new(PieceType::I);
+        piece.x = x;
+        game.current_piece = Some(piece);
+        game.hard_drop();
+    }
+
+    #[test]
+    fn test_combo_and_back_to_back_tracking() {
+        let mut game = Game::new();
+        // Two rows with a 4-wide gap on the right, cleared by two I pieces
+        fill_row(&mut game, 0, &[6, 7, 8, 9]);
+        fill_row(&mut game, 1, &[6, 7, 8, 9]);
+        fill_row(&mut game, 2, &[0, 1, 2, 3, 6, 7, 8, 9]);
+
+        drop_i(&mut game, 7);
+        assert_eq!(game.combo, 1);
+        assert!(!game.back_to_back);
+
+        drop_i(&mut game, 7);
+        assert_eq!(game.combo, 2);
+
+        // Nothing cleared: combo resets, B2B state is kept
+        drop_i(&mut game, 1);
+        assert_eq!(game.combo, 0);
+    }
+
+    fn tetris_well(game: &mut Game) {
+        for y in 0..4 {
+            fill_row(game, y, &[9]);
+        }
+        let mut piece = Piece::new(PieceType::I);
+        piece.x = 8;
+        piece.y = 2;
+        piece.rotation = Rotation::R90;
+        game.current_piece = Some(piece);
+    }
+
+    #[test]
+    fn test_perfect_clear_bonus() {
+        let mut game = Game::new();
+        tetris_well(&mut game);
+        game.hard_drop();
+
+        assert!(game.board.is_empty());
+        assert_eq!(game.score, 800 + 2000);
+        assert!(game.take_perfect_clear());
+        assert!(!game.take_perfect_clear());
+    }
+
+    #[test]
+    fn test_back_to_back_tetris_perfect_clear() {
+        let mut game = Game::new();
+        game.back_to_back = true;
+        tetris_well(&mut game);
+        game.hard_drop();
+
+        assert_eq!(game.score, 1200 + 3200);
+    }
+
+    #[test]
+    fn test_start_level() {
+        let game = Game::with_config(GameConfig {
+            gravity: GravityKind::Guideline,
+            start_level: 5,
+            ..GameConfig::default()
+        });
+        assert_eq!(game.level, 5);
+        assert_eq!(game.start_level(), 5);
+        assert_eq!(game.gravity(), crate::gravity::Guideline.gravity(5));
+    }
+
+    #[test]
+    fn test_scoring_ruleset_from_config() {
+        let mut game = Game::with_config(GameConfig {
+            scoring: ScoringKind::Nes,
+            ..GameConfig::default()
+        });
+        tetris_well(&mut game);
+        game.current_piece.as_mut().unwrap().y += 1;
+        game.soft_drop();
+        game.hard_drop();
+
+        // One soft-dropped row, no hard drop points and no perfect clear bonus
+        assert!(game.board.is_empty());
+        assert_eq!(game.score, 1 + 1200);
+        assert_eq!(game.scoring(), ScoringKind::Nes);
+    }
+
+    #[test]
+    fn test_dig_starts_with_messy_garbage() {
+        let mut game = Game::with_config(GameConfig {
+            seed: Some(3),
+            mode: GameMode::dig(10, false),
+            ..GameConfig::default()
+        });
+        assert_eq!(game.board.garbage_rows(), 10);
+        let holes: Vec<i32> = (0..10)
+            .map(|y| {
+                (0..DEFAULT_WIDTH as i32)
+                    .find(|&x| game.board.get(x, y) == Some(Cell::Empty))
+                    .unwrap()
+            })
+            .collect();
+        assert!(holes.windows(2).all(|pair| pair[0] != pair[1]));
+
+        // The piece is pushed up when garbage rises into it
+        let piece = game.current_piece.as_mut().unwrap();
+        piece.y = 10;
+        assert!(game.add_garbage(3));
+        assert_eq!(game.current_piece.as_ref().unwrap().y, 13);
+        assert_eq!(game.board.garbage_rows(), 13);
+    }
+
+    #[test]
+    fn test_tetris_starts_back_to_back() {
+        let mut game = Game::new();
+        for y in 0..4 {
+            fill_row(&mut game, y, &[9]);
+        }
+        let mut piece = Piece::new(PieceType::I);
+        piece.x = 8;
+        piece.y = 5;
+        piece.rotation = Rotation::R90;
+        game.current_piece = Some(piece);
+        game.hard_drop();
+
+        assert_eq!(game.last_clear, ClearKind::Tetris);
+        assert!(game.back_to_back);
+        assert_eq!(game.combo, 1);
+    }
+
+    fn fill_row(game: &mut Game, y: i32, holes: &[i32]) {
+        for x in 0..DEFAULT_WIDTH as i32 {
+            if !holes.contains(&x) {
+                game.board.set(x, y, Cell::O);
+            }
+        }
+    }
+
+    fn place_t(game: &mut Game, x: i32, y: i32, rotation: Rotation) {
+        let mut piece = Piece::new(PieceType::T);
+        piece.x = x;
+        piece.y = y;
+        piece.rotation = rotation;
+        game.current_piece = Some(piece);
+    }
+
+    #[test]
+    fn test_t_spin_double() {
+        let mut game = Game::new();
+        fill_row(&mut game, 0, &[4]);
+        fill_row(&mut game, 1, &[3, 4, 5]);
+        game.board.set(3, 2, Cell::O); // Overhang
+        place_t(&mut game, 4, 1, Rotation::R90);
+
+        assert_eq!(game.rotate(Turn::Clockwise), MoveResult::Success);
+        game.hard_drop();
+
+        assert_eq!(game.last_clear, ClearKind::TSpinDouble);
+        assert_eq!(game.lines_cleared, 2);
+        assert_eq!(game.score, 1200);
+    }
+
+    #[test]
+    fn test_t_spin_requires_rotation_last() {
+        let mut game = Game::new();
+        fill_row(&mut game, 0, &[4]);
+        fill_row(&mut game, 1, &[3, 4, 5]);
+        game.board.set(3, 2, Cell::O);
+        place_t(&mut game, 4, 2, Rotation::R180);
+
+        // Dropped straight in: same corners, but no T-spin
+        game.hard_drop();
+        assert_eq!(game.last_clear, ClearKind::Double);
+    }
+
+    #[test]
+    fn test_t_spin_mini() {
+        let mut game = Game::new();
+        game.board.set(0, 1, Cell::O);
+        place_t(&mut game, 1, 0, Rotation::R0);
+        game.last_kick = Some((Turn::Clockwise, 1));
+
+        game.hard_drop();
+        assert_eq!(game.last_clear, ClearKind::TSpinMini);
+    }
+
+    #[test]
+    fn test_last_kick_upgrades_mini() {
+        let mut game = Game::new();
+        game.board.set(0, 1, Cell::O);
+        fill_row(&mut game, 0, &[0, 1, 2]);
+        place_t(&mut game, 1, 0, Rotation::R0);
+        game.last_kick = Some((Turn::CounterClockwise, TST_KICK_INDEX));
+
+        game.hard_drop();
+        assert_eq!(game.last_clear, ClearKind::TSpinSingle);
+    }
+
+    #[test]
+    fn test_180_kick_keeps_mini() {
+        let mut game = Game::new();
+        game.board.set(0, 1, Cell::O);
+        fill_row(&mut game, 0, &[0, 1, 2]);
+        place_t(&mut game, 1, 0, Rotation::R0);
+        game.last_kick = Some((Turn::Half, TST_KICK_INDEX));
+
+        game.hard_drop();
+        assert_eq!(game.last_clear, ClearKind::TSpinMiniSingle);
     }
 }
@@ -1,13 +1,29 @@
 pub mod app;
 pub mod board;
 pub mod controller;
+pub mod events;
 pub mod game;
+pub mod gravity;
 pub mod input;
+pub mod mode;
 pub mod piece;
+pub mod randomizer;
 pub mod render;
+pub mod replay;
+pub mod rng;
+pub mod rotation;
+pub mod scoring;
+pub mod snapshot;
+pub mod stats;
 pub mod webgl;
 
-use controller::GameController;
+use controller::{EntryDelay, GameController};
+use gravity::GravityKind;
+use mode::GameMode;
+use randomizer::{PieceRandomizer, ScriptedRandomizer};
+use replay::Replay;
+use rotation::RotationKind;
+use scoring::{ClearKind, ScoringKind};
 use wasm_bindgen::prelude::*;
 
 // Re-export TetrisApp for easy access
@@ -34,8 +50,166 @@ impl Tetris {
         self.controller.update(delta_ms)
     }
 
+    /// Start a new game whose piece sequence is determined by `seed`
+    pub fn start_with_seed(&mut self, seed: u64) {
+        self.controller.start_with_seed(seed);
+    }
+
+    /// Use `seed` for every following start/restart (e.g. daily challenges)
+    pub fn set_seed(&mut self, seed: u64) {
+        self.controller.config.seed = Some(seed);
+    }
+
+    /// Go back to a fresh random seed for each game
+    pub fn clear_seed(&mut self) {
+        self.controller.config.seed = None;
+    }
+
+    /// Get the seed of the current game (0 before the first game)
+    pub fn get_seed(&self) -> u64 {
+        self.controller.game.as_ref().map(|g| g.seed).unwrap_or(0)
+    }
+
+    /// Choose the piece generator for following games:
+    /// 0=7-bag, 1=14-bag, 2=pure random, 3=NES, 4=TGM1, 5=TGM2.
+    /// Returns false for an unknown kind.
+    pub fn set_randomizer(&mut self, kind: u8) -> bool {
+        match PieceRandomizer::from_u8(kind) {
+            Some(randomizer) => {
+                self.controller.config.randomizer = randomizer;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Choose the rotation system for following games:
+    /// 0=SRS, 1=SRS+, 2=ARS, 3=NES, 4=Sega.
+    /// Returns false for an unknown kind.
+    pub fn set_rotation_system(&mut self, kind: u8) -> bool {
+        match RotationKind::from_u8(kind) {
+            Some(rotation) => {
+                self.controller.config.rotation = rotation;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Choose the scoring rules for following games: 0=Guideline, 1=NES, 2=TGM.
+    /// Returns false for an unknown kind.
+    pub fn set_scoring(&mut self, kind: u8) -> bool {
+        match ScoringKind::from_u8(kind) {
+            Some(scoring) => {
+                self.controller.config.scoring = scoring;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Ruleset name of the current game ("guideline", "nes" or "tgm") for tagging high scores
+    pub fn get_ruleset(&self) -> String {
+        self.controller.scoring().name().to_string()
+    }
+
+    /// Choose the gravity curve for following games: 0=Guideline, 1=NES.
+    /// Returns false for an unknown kind.
+    pub fn set_gravity(&mut self, kind: u8) -> bool {
+        match GravityKind::from_u8(kind) {
+            Some(gravity) => {
+                self.controller.config.gravity = gravity;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Use a custom gravity table for following games: G (rows per frame at
+    /// 60 fps) per level from level 1, e.g. [0.02, 0.05, 1, 20].
+    /// Returns false if the table is empty or has a value that isn't positive.
+    pub fn set_gravity_table(&mut self, gravity: Vec<f64>) -> bool {
+        match GravityKind::table(&gravity) {
+            Some(gravity) => {
+                self.controller.config.gravity = gravity;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set the level following games start at (1 to 30)
+    pub fn set_start_level(&mut self, level: u32) {
+        self.controller.config.start_level = level.clamp(1, game::MAX_START_LEVEL);
+    }
+
+    /// Choose the mode of following games: 0=Marathon, 1=Sprint, 2=Ultra (2 minutes),
+    /// 3=Dig, 4=Dig with rising garbage, 5=Master (20G, graded). `goal` is the
+    /// Sprint line goal (20, 40 or 100; 0 for 40) or the Dig garbage rows (5, 10
+    /// or 18; 0 for 10).
+    /// Returns false for an unknown mode or goal.
+    pub fn set_mode(&mut self, kind: u8, goal: u32) -> bool {
+        match GameMode::from_u8(kind, goal) {
+            Some(mode) => {
+                self.controller.config.mode = mode;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Choose the delays between pieces for following games:
+    /// 0=none, 1=guideline, 2=NES, 3=TGM.
+    /// Returns false for an unknown kind.
+    pub fn set_entry_delay(&mut self, kind: u8) -> bool {
+        match EntryDelay::from_u8(kind) {
+            Some(delay) => {
+                self.controller.entry_delay = delay;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Deal a fixed, repeating piece sequence in following games, e.g. "TIOLJSZ".
+    /// Returns false if the letters aren't valid pieces.
+    pub fn set_piece_sequence(&mut self, letters: &str) -> bool {
+        match ScriptedRandomizer::parse(letters) {
+            Some(script) => {
+                self.controller.config.randomizer = PieceRandomizer::Scripted(script);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set the board size for following games; clamped to 4x4 up to 20x40
+    pub fn set_board_size(&mut self, width: usize, height: usize) {
+        self.controller.config.width = width.clamp(board::MIN_WIDTH, board::MAX_WIDTH);
+        self.controller.config.height = height.clamp(board::MIN_HEIGHT, board::MAX_HEIGHT);
+    }
+
+    /// Board width in cells for the current game (or the next one before starting)
+    pub fn get_board_width(&self) -> usize {
+        self.controller
+            .game
+            .as_ref()
+            .map(|g| g.board.width())
+            .unwrap_or(self.controller.config.width)
+    }
+
+    /// Board height in cells, not counting the hidden buffer rows
+    pub fn get_board_height(&self) -> usize {
+        self.controller
+            .game
+            .as_ref()
+            .map(|g| g.board.height())
+            .unwrap_or(self.controller.config.height)
+    }
+
     /// Handle key down event
-    /// key: 0=left, 1=right, 2=down, 3=space, 4=up/x, 5=z, 6=c/shift, 7=p/esc, 8=enter, 9=r
+    /// key: 0=left, 1=right, 2=down, 3=space, 4=up/x, 5=z, 6=c/shift, 7=p/esc, 8=enter, 9=r, 10=a,
+    /// 11=u (undo), 12=y (redo)
     pub fn key_down(&mut self, key: u8) {
         self.controller.key_down(key);
     }
@@ -45,16 +219,127 @@ impl Tetris {
         self.controller.key_up(key);
     }
 
+    /// Returns true once after a lock empties the board (for a celebration effect)
+    pub fn take_perfect_clear(&mut self) -> bool {
+        self.controller.take_perfect_clear()
+    }
+
+    /// Events since the last call as a flat array of `[code, length, payload...]`
+    /// records (see `GameEvent::encode`): 0=spawn, 1=lock, 2=lines, 3=level up,
+    /// 4=hold, 5=game over
+    pub fn drain_events(&mut self) -> Vec<i32> {
+        events::encode_events(&self.controller.drain_events())
+    }
+
     /// Called when window loses focus
     pub fn on_blur(&mut self) {
         self.controller.on_blur();
     }
 
-    /// Get current game state: 0=idle, 1=playing, 2=paused, 3=gameOver
+    /// Get current game state: 0=idle, 1=playing, 2=paused, 3=gameOver, 4=finished
     pub fn get_state(&self) -> u8 {
         self.controller.get_state()
     }
 
+    /// Play time of the current game in ms, not counting pauses
+    pub fn get_elapsed_ms(&self) -> f64 {
+        self.controller.elapsed_ms()
+    }
+
+    /// Play time left in Ultra, frozen while paused (undefined in untimed modes)
+    pub fn get_time_remaining_ms(&self) -> Option<f64> {
+        self.controller.time_remaining_ms()
+    }
+
+    /// Pieces locked in the current game
+    pub fn get_pieces(&self) -> u32 {
+        self.controller.pieces()
+    }
+
+    /// Pieces per second of play time
+    pub fn get_pps(&self) -> f64 {
+        self.controller.pps()
+    }
+
+    /// Lines to clear to finish the current game (0 = no line goal)
+    pub fn get_line_goal(&self) -> u32 {
+        self.controller.mode().line_goal().unwrap_or(0)
+    }
+
+    /// Garbage rows left to clear in Dig
+    pub fn get_garbage_rows(&self) -> u32 {
+        self.controller.garbage_rows()
+    }
+
+    /// Master grade earned so far ("9" up to "S9", or "GM"; undefined in other modes)
+    pub fn get_grade(&self) -> Option<String> {
+        self.controller.grade().map(str::to_string)
+    }
+
+    /// Play time of each Master section finished so far, in ms
+    pub fn get_section_times(&self) -> Vec<f64> {
+        self.controller.section_times().to_vec()
+    }
+
+    /// Recording of the current (or last) game, empty before the first one
+    pub fn export_replay(&self) -> Vec<u8> {
+        self.controller
+            .replay()
+            .map(Replay::to_bytes)
+            .unwrap_or_default()
+    }
+
+    /// Play back a recording from `export_replay`; false if it can't be read
+    pub fn import_replay(&mut self, bytes: &[u8]) -> bool {
+        match Replay::from_bytes(bytes) {
+            Some(replay) => {
+                self.controller.play_replay(&replay);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the current game is a replay being played back
+    pub fn is_replaying(&self) -> bool {
+        self.controller.is_replaying()
+    }
+
+    /// Whether undo was used this game; practice games can't go on the leaderboard
+    pub fn is_practice(&self) -> bool {
+        self.controller.is_practice()
+    }
+
+    /// Whether the current (or last) game was played under the standard
+    /// settings the leaderboards rank (see `Replay::is_standard`)
+    pub fn is_standard(&self) -> bool {
+        self.controller.replay().is_some_and(Replay::is_standard)
+    }
+
+    /// Totals of the current (or last) game as JSON: pieces, keys, piece_counts
+    /// (I, O, T, S, Z, J, L), clears (by clear kind code), lines, max_combo,
+    /// time_ms, pps, kpp and lpm
+    pub fn get_statistics(&self) -> String {
+        self.controller.statistics().to_json()
+    }
+
+    /// Versioned JSON of the whole game state, for resuming after a reload
+    pub fn save_snapshot(&self) -> String {
+        self.controller.save_snapshot()
+    }
+
+    /// Restore a `save_snapshot` (a game in progress comes back paused);
+    /// false for anything else, including snapshots of an older version
+    pub fn load_snapshot(&mut self, json: &str) -> bool {
+        match GameController::load_snapshot(json) {
+            Some(controller) => {
+                self.controller = controller;
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Get score
     pub fn get_score(&self) -> u32 {
         self.controller.get_score()
@@ -70,6 +355,25 @@ impl Tetris {
         self.controller.get_lines()
     }
 
+    /// Get the classification of the most recent lock (see `get_clear_name`)
+    pub fn get_last_clear(&self) -> u8 {
+        self.controller.get_render_state().last_clear
+    }
+
+    /// Get consecutive line-clearing locks (combo step is this minus one)
+    pub fn get_combo(&self) -> u32 {
+        self.controller.game.as_ref().map(|g| g.combo).unwrap_or(0)
+    }
+
+    /// Check if a back-to-back chain is active
+    pub fn is_back_to_back(&self) -> bool {
+        self.controller
+            .game
+            .as_ref()
+            .map(|g| g.back_to_back)
+            .unwrap_or(false)
+    }
+
     // ===== Render data methods =====
     // Returns flat arrays: each cell is 4 bytes [x, y, color, opacity]
 
@@ -78,6 +382,11 @@ impl Tetris {
         self.controller.get_render_state().to_flat_arrays().board
     }
 
+    /// Rows (screen y) being cleared, shown faded until the line-clear delay ends
+    pub fn get_clearing_rows(&self) -> Vec<u8> {
+        self.controller.get_render_state().clearing_rows
+    }
+
     /// Get current piece cells
     pub fn get_piece_cells(&self) -> Vec<u8> {
         self.controller.get_render_state().to_flat_arrays().piece
@@ -93,6 +402,30 @@ impl Tetris {
         self.controller.get_render_state().to_flat_arrays().next
     }
 
+    /// Number of visible next queue slots in the current game
+    pub fn get_queue_length(&self) -> usize {
+        self.controller
+            .game
+            .as_ref()
+            .map(|g| g.preview_count())
+            .unwrap_or(self.controller.config.preview_count)
+    }
+
+    /// Get preview cells for one next queue slot (0 = next piece)
+    pub fn get_queue_cells(&self, slot: usize) -> Vec<u8> {
+        let mut queue = self.controller.get_render_state().to_flat_arrays().queue;
+        if slot < queue.len() {
+            queue.swap_remove(slot)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Set the next queue length (0-6) for following games
+    pub fn set_preview_count(&mut self, count: usize) {
+        self.controller.config.preview_count = count.min(game::MAX_PREVIEWS);
+    }
+
     /// Get hold piece preview cells
     pub fn get_hold_cells(&self) -> Vec<u8> {
         self.controller.get_render_state().to_flat_arrays().hold
@@ -125,12 +458,16 @@ pub const KEY_HOLD: u8 = 6;
 pub const KEY_PAUSE: u8 = 7;
 pub const KEY_START: u8 = 8;
 pub const KEY_RESTART: u8 = 9;
+pub const KEY_ROTATE_180: u8 = 10;
+pub const KEY_UNDO: u8 = 11;
+pub const KEY_REDO: u8 = 12;
 
 // Game state constants for JS
 pub const STATE_IDLE: u8 = 0;
 pub const STATE_PLAYING: u8 = 1;
 pub const STATE_PAUSED: u8 = 2;
 pub const STATE_GAME_OVER: u8 = 3;
+pub const STATE_FINISHED: u8 = 4;
 
 // Cell colors for rendering
 #[wasm_bindgen]
@@ -144,6 +481,13 @@ pub fn get_color(cell_type: u8) -> String {
         5 => "#ff6b6b".to_string(), // Z - Red
         6 => "#4169e1".to_string(), // J - Blue
         7 => "#ff8c00".to_string(), // L - Orange
+        8 => "#6c6c7e".to_string(), // Garbage - Gray
         _ => "#1a1a2e".to_string(),
     }
 }
+
+// Line clear names for rendering: 0=none, 1-4=single..tetris, 5-7=T-spin mini, 8-11=T-spin
+#[wasm_bindgen]
+pub fn get_clear_name(clear_kind: u8) -> String {
+    ClearKind::from_u8(clear_kind).name().to_string()
+}
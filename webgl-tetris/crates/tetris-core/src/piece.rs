@@ -1,4 +1,5 @@
-use crate::board::{Cell, HEIGHT, WIDTH};
+use crate::board::{Board, Cell, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+use crate::rotation::{RotationKind, RotationSystem};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -36,27 +37,6 @@ impl PieceType {
             PieceType::L,
         ]
     }
-
-    /// Get the block offsets for this piece at rotation state 0
-    /// Origin is the rotation center
-    fn base_blocks(self) -> [(i32, i32); 4] {
-        match self {
-            // I piece (horizontal)
-            PieceType::I => [(-1, 0), (0, 0), (1, 0), (2, 0)],
-            // O piece (doesn't rotate)
-            PieceType::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
-            // T piece
-            PieceType::T => [(-1, 0), (0, 0), (1, 0), (0, 1)],
-            // S piece
-            PieceType::S => [(0, 0), (1, 0), (-1, 1), (0, 1)],
-            // Z piece
-            PieceType::Z => [(-1, 0), (0, 0), (0, 1), (1, 1)],
-            // J piece
-            PieceType::J => [(-1, 1), (-1, 0), (0, 0), (1, 0)],
-            // L piece
-            PieceType::L => [(-1, 0), (0, 0), (1, 0), (1, 1)],
-        }
-    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -85,127 +65,80 @@ impl Rotation {
             Rotation::R270 => Rotation::R180,
         }
     }
-}
 
-/// SRS wall kick data for J, L, S, T, Z pieces
-const SRS_KICKS_JLSTZ: [[(i32, i32); 5]; 8] = [
-    // 0 -> R (clockwise from 0)
-    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
-    // R -> 0 (counter-clockwise from R)
-    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
-    // R -> 2 (clockwise from R)
-    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
-    // 2 -> R (counter-clockwise from 2)
-    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
-    // 2 -> L (clockwise from 2)
-    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
-    // L -> 2 (counter-clockwise from L)
-    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
-    // L -> 0 (clockwise from L)
-    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
-    // 0 -> L (counter-clockwise from 0)
-    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
-];
-
-/// SRS wall kick data for I piece
-const SRS_KICKS_I: [[(i32, i32); 5]; 8] = [
-    // 0 -> R
-    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
-    // R -> 0
-    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
-    // R -> 2
-    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
-    // 2 -> R
-    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
-    // 2 -> L
-    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
-    // L -> 2
-    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
-    // L -> 0
-    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
-    // 0 -> L
-    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
-];
+    pub fn half_turn(self) -> Rotation {
+        self.clockwise().clockwise()
+    }
 
-fn get_kick_index(from: Rotation, to: Rotation) -> usize {
-    match (from, to) {
-        (Rotation::R0, Rotation::R90) => 0,
-        (Rotation::R90, Rotation::R0) => 1,
-        (Rotation::R90, Rotation::R180) => 2,
-        (Rotation::R180, Rotation::R90) => 3,
-        (Rotation::R180, Rotation::R270) => 4,
-        (Rotation::R270, Rotation::R180) => 5,
-        (Rotation::R270, Rotation::R0) => 6,
-        (Rotation::R0, Rotation::R270) => 7,
-        _ => 0,
+    /// State reached by applying `turn`
+    pub fn turned(self, turn: Turn) -> Rotation {
+        match turn {
+            Turn::Clockwise => self.clockwise(),
+            Turn::CounterClockwise => self.counter_clockwise(),
+            Turn::Half => self.half_turn(),
+        }
     }
 }
 
+/// A rotation input
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Turn {
+    Clockwise,
+    CounterClockwise,
+    /// 180 degrees
+    Half,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Piece {
     pub piece_type: PieceType,
     pub x: i32,
     pub y: i32,
     pub rotation: Rotation,
+    /// Rotation system giving the piece its shapes
+    #[serde(default)]
+    pub system: RotationKind,
 }
 
 impl Piece {
+    /// SRS piece at the spawn position of a default-sized board
     pub fn new(piece_type: PieceType) -> Self {
         Self {
             piece_type,
-            x: (WIDTH as i32) / 2 - 1,
-            y: HEIGHT as i32, // Spawn in buffer zone
+            x: (DEFAULT_WIDTH as i32) / 2 - 1,
+            y: DEFAULT_HEIGHT as i32, // Spawn in buffer zone
             rotation: Rotation::R0,
+            system: RotationKind::Srs,
         }
     }
 
-    /// Get the absolute positions of all blocks
-    pub fn get_blocks(&self) -> [(i32, i32); 4] {
-        let base = self.piece_type.base_blocks();
-        let mut result = [(0, 0); 4];
-
-        for (i, (bx, by)) in base.iter().enumerate() {
-            let (rx, ry) = self.rotate_block(*bx, *by);
-            result[i] = (self.x + rx, self.y + ry);
+    /// Piece at the spawn position of `board`, in the spawn orientation of `system`
+    pub fn spawn(piece_type: PieceType, board: &Board, system: RotationKind) -> Self {
+        let (x, y) = board.spawn_position();
+        Self {
+            piece_type,
+            x,
+            y,
+            rotation: system.spawn_rotation(piece_type),
+            system,
         }
-
-        result
     }
 
-    /// Rotate a single block offset by the current rotation
-    fn rotate_block(&self, x: i32, y: i32) -> (i32, i32) {
-        match self.rotation {
-            Rotation::R0 => (x, y),
-            Rotation::R90 => (y, -x),
-            Rotation::R180 => (-x, -y),
-            Rotation::R270 => (-y, x),
-        }
+    /// Get the absolute positions of all blocks
+    pub fn get_blocks(&self) -> [(i32, i32); 4] {
+        self.system
+            .blocks(self.piece_type, self.rotation)
+            .map(|(bx, by)| (self.x + bx, self.y + by))
     }
 
-    /// Get SRS wall kicks for a rotation
-    pub fn get_kicks(&self, clockwise: bool) -> &'static [(i32, i32); 5] {
-        let new_rotation = if clockwise {
-            self.rotation.clockwise()
-        } else {
-            self.rotation.counter_clockwise()
-        };
-
-        let kick_index = get_kick_index(self.rotation, new_rotation);
-
-        match self.piece_type {
-            PieceType::I => &SRS_KICKS_I[kick_index],
-            PieceType::O => &[(0, 0), (0, 0), (0, 0), (0, 0), (0, 0)], // O doesn't kick
-            _ => &SRS_KICKS_JLSTZ[kick_index],
-        }
+    /// Get the kicks to try for a rotation, according to the piece's rotation system
+    pub fn get_kicks(&self, board: &Board, turn: Turn) -> &'static [(i32, i32)] {
+        self.system.kicks(board, self, self.rotation.turned(turn))
     }
 
     /// Apply a rotation (without checking collision)
-    pub fn rotate(&mut self, clockwise: bool) {
-        self.rotation = if clockwise {
-            self.rotation.clockwise()
-        } else {
-            self.rotation.counter_clockwise()
-        };
+    pub fn rotate(&mut self, turn: Turn) {
+        self.rotation = self.rotation.turned(turn);
     }
 
     /// Get blocks after applying a move
@@ -219,27 +152,10 @@ impl Piece {
     }
 
     /// Get blocks after applying rotation and kick
-    pub fn get_blocks_after_rotation(&self, clockwise: bool, kick: (i32, i32)) -> [(i32, i32); 4] {
-        let new_rotation = if clockwise {
-            self.rotation.clockwise()
-        } else {
-            self.rotation.counter_clockwise()
-        };
-
-        let base = self.piece_type.base_blocks();
-        let mut result = [(0, 0); 4];
-
-        for (i, (bx, by)) in base.iter().enumerate() {
-            let (rx, ry) = match new_rotation {
-                Rotation::R0 => (*bx, *by),
-                Rotation::R90 => (*by, -*bx),
-                Rotation::R180 => (-*bx, -*by),
-                Rotation::R270 => (-*by, *bx),
-            };
-            result[i] = (self.x + rx + kick.0, self.y + ry + kick.1);
-        }
-
-        result
+    pub fn get_blocks_after_rotation(&self, turn: Turn, kick: (i32, i32)) -> [(i32, i32); 4] {
+        self.system
+            .blocks(self.piece_type, self.rotation.turned(turn))
+            .map(|(bx, by)| (self.x + bx + kick.0, self.y + by + kick.1))
     }
 }
 
@@ -267,6 +183,17 @@ mod tests {
         assert_eq!(rot, Rotation::R0);
     }
 
+    #[test]
+    fn test_half_turn() {
+        assert_eq!(Rotation::R0.turned(Turn::Half), Rotation::R180);
+        assert_eq!(Rotation::R90.turned(Turn::Half), Rotation::R270);
+        assert_eq!(Rotation::R270.half_turn(), Rotation::R90);
+        let mut piece = Piece::new(PieceType::T);
+        piece.rotate(Turn::Half);
+        piece.rotate(Turn::Half);
+        assert_eq!(piece.rotation, Rotation::R0);
+    }
+
     #[test]
     fn test_t_piece_blocks() {
         let mut piece = Piece::new(PieceType::T);
@@ -0,0 +1,128 @@
+use crate::piece::PieceType;
+use crate::scoring::ClearKind;
+use serde::{Deserialize, Serialize};
+
+/// Number of `ClearKind` values
+const CLEAR_KINDS: usize = 12;
+
+/// Running totals of one game. `Game` counts its locks; the controller counts
+/// key presses and sets the play time.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Statistics {
+    /// Pieces locked
+    pub pieces: u32,
+    /// Key presses that acted on a piece: moves, rotations, drops and holds
+    pub keys: u32,
+    /// Pieces locked of each type, in `PieceType::all()` order
+    pub piece_counts: [u32; 7],
+    /// Locks of each kind, indexed by `ClearKind as u8` (0 for locks that
+    /// cleared nothing without a spin)
+    pub clears: [u32; CLEAR_KINDS],
+    /// Lines cleared
+    pub lines: u32,
+    /// Longest run of line-clearing locks
+    pub max_combo: u32,
+    /// Play time (ms), not counting pauses; set by the controller
+    pub time_ms: f64,
+}
+
+impl Statistics {
+    /// Count a lock of `piece` classified as `clear`, `combo` clears into a combo
+    pub fn record_lock(&mut self, piece: PieceType, clear: ClearKind, lines: u32, combo: u32) {
+        self.pieces += 1;
+        self.piece_counts[piece as usize] += 1;
+        self.clears[clear as usize] += 1;
+        self.lines += lines;
+        self.max_combo = self.max_combo.max(combo);
+    }
+
+    /// Count a key press that acted on a piece
+    pub fn record_key(&mut self) {
+        self.keys += 1;
+    }
+
+    /// Pieces locked of `piece`'s type
+    pub fn piece_count(&self, piece: PieceType) -> u32 {
+        self.piece_counts[piece as usize]
+    }
+
+    /// Locks classified as `clear`
+    pub fn clear_count(&self, clear: ClearKind) -> u32 {
+        self.clears[clear as usize]
+    }
+
+    /// Pieces per second
+    pub fn pps(&self) -> f64 {
+        per(self.pieces, self.time_ms / 1000.0)
+    }
+
+    /// Keys per piece
+    pub fn kpp(&self) -> f64 {
+        per(self.keys, self.pieces as f64)
+    }
+
+    /// Lines per minute
+    pub fn lpm(&self) -> f64 {
+        per(self.lines, self.time_ms / 60_000.0)
+    }
+
+    /// JSON of the totals along with `pps`, `kpp` and `lpm`
+    pub fn to_json(&self) -> String {
+        #[derive(Serialize)]
+        struct Summary<'a> {
+            #[serde(flatten)]
+            stats: &'a Statistics,
+            pps: f64,
+            kpp: f64,
+            lpm: f64,
+        }
+
+        serde_json::to_string(&Summary {
+            stats: self,
+            pps: self.pps(),
+            kpp: self.kpp(),
+            lpm: self.lpm(),
+        })
+        .unwrap_or_default()
+    }
+}
+
+/// `count` over `total`, 0 before there is any
+fn per(count: u32, total: f64) -> f64 {
+    if total > 0.0 {
+        count as f64 / total
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_and_rates() {
+        let mut stats = Statistics::default();
+        for _ in 0..5 {
+            stats.record_key();
+        }
+        stats.record_lock(PieceType::T, ClearKind::TSpinDouble, 2, 1);
+        stats.record_lock(PieceType::I, ClearKind::Tetris, 4, 2);
+        stats.record_lock(PieceType::T, ClearKind::None, 0, 0);
+        stats.time_ms = 2000.0;
+
+        assert_eq!(stats.piece_count(PieceType::T), 2);
+        assert_eq!(stats.piece_count(PieceType::O), 0);
+        assert_eq!(stats.clear_count(ClearKind::TSpinDouble), 1);
+        assert_eq!(stats.clear_count(ClearKind::None), 1);
+        assert_eq!(stats.max_combo, 2);
+        assert_eq!(stats.pps(), 1.5);
+        assert_eq!(stats.lpm(), 180.0);
+        assert!((stats.kpp() - 5.0 / 3.0).abs() < 1e-9);
+
+        let json: serde_json::Value = serde_json::from_str(&stats.to_json()).unwrap();
+        assert_eq!(json["pieces"], 3);
+        assert_eq!(json["pps"], 1.5);
+        assert_eq!(Statistics::default().kpp(), 0.0);
+    }
+}
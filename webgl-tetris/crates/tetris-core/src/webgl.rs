@@ -1,8 +1,8 @@
 use wasm_bindgen::prelude::*;
 use web_sys::{HtmlCanvasElement, WebGlBuffer, WebGlProgram, WebGlRenderingContext, WebGlShader};
 
-// Tetromino colors [R, G, B]
-const COLORS: [[f32; 3]; 8] = [
+// Cell colors [R, G, B]
+const COLORS: [[f32; 3]; 9] = [
     [0.102, 0.102, 0.180], // 0: Empty (#1a1a2e)
     [0.000, 0.961, 1.000], // 1: I - Cyan
     [1.000, 0.843, 0.000], // 2: O - Yellow
@@ -11,8 +11,12 @@ const COLORS: [[f32; 3]; 8] = [
     [1.000, 0.420, 0.420], // 5: Z - Red
     [0.255, 0.412, 0.882], // 6: J - Blue
     [1.000, 0.549, 0.000], // 7: L - Orange
+    [0.424, 0.424, 0.494], // 8: Garbage - Gray (#6c6c7e)
 ];
 
+/// Height of one preview slot in cells: a 2-row piece plus a half-row gap
+pub const PREVIEW_SLOT_ROWS: f32 = 2.5;
+
 const VERTEX_SHADER: &str = r#"
     precision mediump float;
 
@@ -113,6 +117,7 @@ const GRID_FRAGMENT_SHADER: &str = r#"
 "#;
 
 pub struct WebGlRenderer {
+    canvas: HtmlCanvasElement,
     gl: WebGlRenderingContext,
     cell_program: WebGlProgram,
     grid_program: WebGlProgram,
@@ -163,7 +168,14 @@ impl WebGlRenderer {
         let width = canvas.width() as f32;
         let height = canvas.height() as f32;
 
+        // Center the grid in whatever padding the canvas leaves around it
+        let grid_offset = (
+            (width - grid_width as f32 * cell_size) / 2.0,
+            (height - grid_height as f32 * cell_size) / 2.0,
+        );
+
         Ok(Self {
+            canvas: canvas.clone(),
             gl,
             cell_program,
             grid_program,
@@ -173,7 +185,7 @@ impl WebGlRenderer {
             cell_size,
             grid_width,
             grid_height,
-            grid_offset: (0.0, 0.0),
+            grid_offset,
         })
     }
 
@@ -242,6 +254,25 @@ impl WebGlRenderer {
         self.grid_offset = (x, y);
     }
 
+    pub fn grid_size(&self) -> (u32, u32) {
+        (self.grid_width, self.grid_height)
+    }
+
+    /// Resize the grid, growing or shrinking the canvas to fit it with the same padding
+    pub fn set_grid_size(&mut self, grid_width: u32, grid_height: u32, cell_size: f32) {
+        let width = grid_width as f32 * cell_size + 2.0 * self.grid_offset.0;
+        let height = grid_height as f32 * cell_size + 2.0 * self.grid_offset.1;
+        self.canvas.set_width(width as u32);
+        self.canvas.set_height(height as u32);
+        self.gl.viewport(0, 0, width as i32, height as i32);
+
+        self.width = width;
+        self.height = height;
+        self.cell_size = cell_size;
+        self.grid_width = grid_width;
+        self.grid_height = grid_height;
+    }
+
     pub fn clear(&self) {
         self.gl.clear_color(0.039, 0.039, 0.102, 1.0);
         self.gl.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
@@ -390,7 +421,19 @@ impl PreviewRenderer {
 
     pub fn render_cells(&self, cells: &[u8], corner_radius: f32) {
         self.clear();
+        self.draw_cells(cells, corner_radius, 0.0);
+    }
+
+    /// Render a vertical stack of previews, one flat cell array per slot, top to bottom
+    pub fn render_stack(&self, slots: &[Vec<u8>], corner_radius: f32) {
+        self.clear();
+        for (i, cells) in slots.iter().enumerate() {
+            let offset_y = i as f32 * PREVIEW_SLOT_ROWS * self.cell_size;
+            self.draw_cells(cells, corner_radius, offset_y);
+        }
+    }
 
+    fn draw_cells(&self, cells: &[u8], corner_radius: f32, offset_y: f32) {
         let gl = &self.gl;
         gl.use_program(Some(&self.cell_program));
 
@@ -402,7 +445,7 @@ impl PreviewRenderer {
         gl.uniform1f(cell_size_loc.as_ref(), self.cell_size);
 
         let grid_offset_loc = gl.get_uniform_location(&self.cell_program, "u_gridOffset");
-        gl.uniform2f(grid_offset_loc.as_ref(), 0.0, 0.0);
+        gl.uniform2f(grid_offset_loc.as_ref(), 0.0, offset_y);
 
         let corner_loc = gl.get_uniform_location(&self.cell_program, "u_cornerRadius");
         gl.uniform1f(corner_loc.as_ref(), corner_radius);
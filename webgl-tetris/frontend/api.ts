@@ -3,15 +3,17 @@ export interface Score {
   score: number;
   level: number;
   lines: number;
+  // Scoring ruleset the game was played under ("guideline", "nes" or "tgm")
+  ruleset: string;
   timestamp: number;
 }
 
 // Shared API endpoint for all Tetris games
 const API_BASE = "https://tetris-api.mgavriliu.deno.net/api";
 
-export async function getHighScores(): Promise<Score[]> {
+export async function getHighScores(ruleset: string): Promise<Score[]> {
   try {
-    const response = await fetch(`${API_BASE}/scores`);
+    const response = await fetch(`${API_BASE}/scores?ruleset=${encodeURIComponent(ruleset)}`);
     if (!response.ok) {
       throw new Error(`HTTP ${response.status}`);
     }
@@ -22,7 +24,8 @@ export async function getHighScores(): Promise<Score[]> {
   }
 }
 
-export async function submitScore(score: Omit<Score, "timestamp">): Promise<boolean> {
+// Submissions carry the game's replay (base64) so the server can re-simulate it
+export async function submitScore(score: Omit<Score, "timestamp"> & { replay: string }): Promise<boolean> {
   try {
     const response = await fetch(`${API_BASE}/scores`, {
       method: "POST",
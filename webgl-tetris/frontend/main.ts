@@ -1,4 +1,4 @@
-import init, { TetrisApp } from "../../pkg/tetris_core.js";
+import init, { TetrisApp, get_clear_name } from "../../pkg/tetris_core.js";
 import { getHighScores, submitScore, type Score } from "./api.ts";
 
 // Game states
@@ -6,23 +6,226 @@ const STATE_IDLE = 0;
 const STATE_PLAYING = 1;
 const STATE_PAUSED = 2;
 const STATE_GAME_OVER = 3;
+const STATE_FINISHED = 4;
+
+// localStorage key of the game saved on leaving the page
+const SNAPSHOT_KEY = "webgl-tetris-snapshot";
 
 // Constants
 const CELL_SIZE = 30;
+// Default board size; ?width=<4-20>&height=<4-40> resizes the canvas from Rust
 const BOARD_WIDTH = 10;
 const BOARD_HEIGHT = 20;
 const PREVIEW_CELL_SIZE = CELL_SIZE * 0.8;
+const PREVIEW_SLOT_ROWS = 2.5; // 2-row piece plus a half-row gap
+const DEFAULT_PREVIEWS = 5;
+const MAX_PREVIEWS = 6;
+
+// Next queue length, optionally set with ?previews=<0-6>
+const previewCount = readPreviewCount();
 
 // DOM elements
 let scoreEl: HTMLElement;
 let levelEl: HTMLElement;
 let linesEl: HTMLElement;
+let timeEl: HTMLElement;
+let comboEl: HTMLElement;
+let clearLabelEl: HTMLElement;
+let b2bLabelEl: HTMLElement;
 let scoresEl: HTMLElement;
+let statsEl: HTMLElement;
 let gameOverOverlay: HTMLDivElement;
 
 // Game app
 let app: TetrisApp;
 
+const RANDOMIZERS: Record<string, number> = { bag7: 0, bag14: 1, random: 2, nes: 3, tgm1: 4, tgm2: 5 };
+
+// Optional ?randomizer=<name> or ?pieces=<letters> URL parameters for training setups
+function applyRandomizerParams(
+  setRandomizer: (kind: number) => boolean,
+  setPieceSequence: (letters: string) => boolean,
+) {
+  const params = new URLSearchParams(window.location.search);
+  const pieces = params.get("pieces");
+  const name = params.get("randomizer");
+  if (pieces !== null && !setPieceSequence(pieces)) {
+    console.warn(`Ignoring invalid piece sequence: ${pieces}`);
+  } else if (pieces === null && name !== null && !(name in RANDOMIZERS && setRandomizer(RANDOMIZERS[name]))) {
+    console.warn(`Unknown randomizer: ${name}`);
+  }
+}
+
+const ROTATION_SYSTEMS: Record<string, number> = { srs: 0, srsplus: 1, ars: 2, nes: 3, sega: 4 };
+
+// Optional ?rotation=<name> URL parameter
+function applyRotationParam(setRotationSystem: (kind: number) => boolean) {
+  const name = new URLSearchParams(window.location.search).get("rotation");
+  if (name !== null && !(name in ROTATION_SYSTEMS && setRotationSystem(ROTATION_SYSTEMS[name]))) {
+    console.warn(`Unknown rotation system: ${name}`);
+  }
+}
+
+const GRAVITY_CURVES: Record<string, number> = { guideline: 0, nes: 1 };
+
+// Optional ?gravity=<name> or ?gravity=<G per level, comma-separated> and ?level=<start level> URL parameters
+function applyGravityParams(
+  setGravity: (kind: number) => boolean,
+  setGravityTable: (gravity: Float64Array) => boolean,
+  setStartLevel: (level: number) => void,
+) {
+  const params = new URLSearchParams(window.location.search);
+  const name = params.get("gravity");
+  if (name !== null) {
+    const ok = name in GRAVITY_CURVES
+      ? setGravity(GRAVITY_CURVES[name])
+      : setGravityTable(new Float64Array(name.split(",").map(Number)));
+    if (!ok) {
+      console.warn(`Unknown gravity curve: ${name}`);
+    }
+  }
+  const level = parseInt(params.get("level") ?? "", 10);
+  if (!Number.isNaN(level)) {
+    setStartLevel(level);
+  }
+}
+
+const MODES: Record<string, number> = { marathon: 0, sprint: 1, ultra: 2, dig: 3, "dig-rising": 4, master: 5 };
+
+// Optional ?mode=<name> URL parameter, with ?lines=20|40|100 for Sprint or 5|10|18 garbage rows for Dig
+function applyModeParam(setMode: (kind: number, goal: number) => boolean) {
+  const params = new URLSearchParams(window.location.search);
+  const name = params.get("mode");
+  const goal = parseInt(params.get("lines") ?? "0", 10);
+  if (name !== null && !(name in MODES && setMode(MODES[name], Number.isNaN(goal) ? 0 : goal))) {
+    console.warn(`Unknown mode: ${name}`);
+  }
+}
+
+// m:ss.mmm
+function formatTime(ms: number): string {
+  const total = Math.floor(ms);
+  const minutes = Math.floor(total / 60000);
+  const seconds = Math.floor((total % 60000) / 1000);
+  return `${minutes}:${String(seconds).padStart(2, "0")}.${String(total % 1000).padStart(3, "0")}`;
+}
+
+function renderLines(lines: number) {
+  const goal = app.get_line_goal();
+  const garbage = app.get_garbage_rows();
+  linesEl.textContent = goal > 0
+    ? `${lines}/${goal}`
+    : garbage > 0 ? `${lines} (${garbage} left)` : String(lines);
+  // Ultra counts down, other modes count up
+  timeEl.textContent = formatTime(app.get_time_remaining_ms() ?? app.get_elapsed_ms());
+  renderStats();
+}
+
+interface Statistics {
+  pieces: number;
+  keys: number;
+  // Locked pieces by type, in PIECE_NAMES order
+  piece_counts: number[];
+  // Locks by clear kind code: 1-4 single to Tetris, 5-11 T-spins
+  clears: number[];
+  lines: number;
+  max_combo: number;
+  time_ms: number;
+  pps: number;
+  kpp: number;
+  lpm: number;
+}
+
+const PIECE_NAMES = ["I", "O", "T", "S", "Z", "J", "L"];
+
+// Live rates while playing; the full breakdown once the game has ended
+function renderStats() {
+  const stats: Statistics = JSON.parse(app.get_statistics());
+  const rows: [string, string][] = [
+    ["PPS", stats.pps.toFixed(2)],
+    ["KPP", stats.kpp.toFixed(2)],
+    ["LPM", stats.lpm.toFixed(1)],
+  ];
+  const state = app.get_state();
+  if (state === STATE_GAME_OVER || state === STATE_FINISHED) {
+    rows.unshift(["Pieces", String(stats.pieces)], ["Time", formatTime(stats.time_ms)]);
+    rows.push(
+      // Combo counts from the second consecutive clear
+      ["Max combo", String(Math.max(stats.max_combo - 1, 0))],
+      ["Singles", String(stats.clears[1])],
+      ["Doubles", String(stats.clears[2])],
+      ["Triples", String(stats.clears[3])],
+      ["Tetrises", String(stats.clears[4])],
+      ["T-spins", String(stats.clears.slice(5).reduce((sum, count) => sum + count, 0))],
+      ...PIECE_NAMES.map((name, i): [string, string] => [name, String(stats.piece_counts[i])]),
+    );
+  }
+  statsEl.innerHTML = rows
+    .map(([label, value]) => `<div class="stat-row"><span>${label}</span><span>${value}</span></div>`)
+    .join("");
+}
+
+// Master grade, empty in other modes
+function gradeSummary(): string {
+  const grade = app.get_grade();
+  return grade === undefined ? "" : `Grade ${grade}`;
+}
+
+// Result line for a finished game: the score for Ultra, the time for Sprint, Dig and Master
+function finishSummary(): string {
+  const result = app.get_time_remaining_ms() === undefined
+    ? formatTime(app.get_elapsed_ms())
+    : `${app.get_score().toLocaleString()} pts`;
+  const grade = gradeSummary();
+  return `${grade ? `${grade} · ` : ""}${result} · ${app.get_pieces()} pieces · ${app.get_pps().toFixed(2)} PPS`;
+}
+
+const SCORING_RULES: Record<string, number> = { guideline: 0, nes: 1, tgm: 2 };
+
+// Optional ?scoring=<name> URL parameter
+function applyScoringParam(setScoring: (kind: number) => boolean) {
+  const name = new URLSearchParams(window.location.search).get("scoring");
+  if (name !== null && !(name in SCORING_RULES && setScoring(SCORING_RULES[name]))) {
+    console.warn(`Unknown scoring rules: ${name}`);
+  }
+}
+
+const ENTRY_DELAYS: Record<string, number> = { none: 0, guideline: 1, nes: 2, tgm: 3 };
+
+// Optional ?delays=<name> URL parameter for ARE and line-clear delays
+function applyEntryDelayParam(setEntryDelay: (kind: number) => boolean) {
+  const name = new URLSearchParams(window.location.search).get("delays");
+  if (name !== null && !(name in ENTRY_DELAYS && setEntryDelay(ENTRY_DELAYS[name]))) {
+    console.warn(`Unknown entry delay preset: ${name}`);
+  }
+}
+
+function readPreviewCount(): number {
+  const param = new URLSearchParams(window.location.search).get("previews");
+  const count = param === null ? DEFAULT_PREVIEWS : parseInt(param, 10);
+  return Number.isNaN(count) ? DEFAULT_PREVIEWS : Math.min(Math.max(count, 0), MAX_PREVIEWS);
+}
+
+function applyBoardSizeParams(setBoardSize: (width: number, height: number) => void) {
+  const params = new URLSearchParams(window.location.search);
+  const width = parseInt(params.get("width") ?? "", 10);
+  const height = parseInt(params.get("height") ?? "", 10);
+  if (!Number.isNaN(width) || !Number.isNaN(height)) {
+    setBoardSize(Number.isNaN(width) ? BOARD_WIDTH : width, Number.isNaN(height) ? BOARD_HEIGHT : height);
+  }
+}
+
+// Optional ?seed=<u64> URL parameter for reproducible piece sequences
+function applySeedParam(setSeed: (seed: bigint) => void) {
+  const param = new URLSearchParams(window.location.search).get("seed");
+  if (param === null) return;
+  try {
+    setSeed(BigInt.asUintN(64, BigInt(param)));
+  } catch {
+    console.warn(`Ignoring invalid seed: ${param}`);
+  }
+}
+
 async function main() {
   try {
     console.log("Initializing WASM...");
@@ -33,6 +236,23 @@ async function main() {
 
     console.log("Creating TetrisApp...");
     app = new TetrisApp(boardCanvas, nextCanvas, holdCanvas);
+    app.set_preview_count(previewCount);
+    applySeedParam((seed) => app.set_seed(seed));
+    applyBoardSizeParams((width, height) => app.set_board_size(width, height));
+    applyRandomizerParams(
+      (kind) => app.set_randomizer(kind),
+      (letters) => app.set_piece_sequence(letters),
+    );
+    applyRotationParam((kind) => app.set_rotation_system(kind));
+    applyEntryDelayParam((kind) => app.set_entry_delay(kind));
+    applyScoringParam((kind) => app.set_scoring(kind));
+    applyModeParam((kind, goal) => app.set_mode(kind, goal));
+    applyGravityParams(
+      (kind) => app.set_gravity(kind),
+      (gravity) => app.set_gravity_table(gravity),
+      (level) => app.set_start_level(level),
+    );
+    resumeSavedGame();
 
     console.log("Setting up callbacks...");
     setupCallbacks();
@@ -72,7 +292,7 @@ function createUI(): {
 
   const nextCanvas = document.createElement("canvas");
   nextCanvas.width = 4 * PREVIEW_CELL_SIZE;
-  nextCanvas.height = 2 * PREVIEW_CELL_SIZE;
+  nextCanvas.height = Math.max(previewCount * PREVIEW_SLOT_ROWS - 0.5, 0) * PREVIEW_CELL_SIZE;
   nextCanvas.className = "preview-canvas";
 
   const holdCanvas = document.createElement("canvas");
@@ -91,6 +311,10 @@ function createUI(): {
       <div class="panel-label">HOLD</div>
       <div class="preview-container" id="hold-container"></div>
     </div>
+    <div class="panel-section">
+      <div class="panel-label">STATS</div>
+      <div id="game-stats" class="game-stats"></div>
+    </div>
     <div class="panel-section">
       <div class="panel-label">HIGH SCORES</div>
       <div id="ingame-scores" class="ingame-scores"></div>
@@ -116,6 +340,16 @@ function createUI(): {
       <div class="panel-label">LINES</div>
       <div class="stat-value" id="lines">0</div>
     </div>
+    <div class="panel-section">
+      <div class="panel-label">TIME</div>
+      <div class="stat-value" id="time">0:00.000</div>
+    </div>
+    <div class="panel-section">
+      <div class="panel-label">COMBO</div>
+      <div class="stat-value" id="combo">0</div>
+      <div class="clear-label" id="clear-label"></div>
+      <div class="clear-label" id="b2b-label"></div>
+    </div>
   `;
 
   // Board wrapper for game over overlay
@@ -128,7 +362,8 @@ function createUI(): {
   gameOverOverlay.className = "game-over-overlay";
   gameOverOverlay.innerHTML = `
     <div class="game-over-text">GAME OVER</div>
-    <div class="game-over-subtext">Press R to restart</div>
+    <div class="game-over-subtext game-over-detail"></div>
+    <div class="game-over-subtext">Press R to restart, S to save replay</div>
   `;
   gameOverOverlay.style.display = "none";
   boardWrapper.appendChild(gameOverOverlay);
@@ -144,7 +379,12 @@ function createUI(): {
   scoreEl = document.getElementById("score")!;
   levelEl = document.getElementById("level")!;
   linesEl = document.getElementById("lines")!;
+  timeEl = document.getElementById("time")!;
+  comboEl = document.getElementById("combo")!;
+  clearLabelEl = document.getElementById("clear-label")!;
+  b2bLabelEl = document.getElementById("b2b-label")!;
   scoresEl = document.getElementById("ingame-scores")!;
+  statsEl = document.getElementById("game-stats")!;
 
   return { boardCanvas, nextCanvas, holdCanvas };
 }
@@ -153,8 +393,12 @@ function setupCallbacks() {
   // State change callback
   app.set_on_state_change((newState: number, oldState: number) => {
     if (newState === STATE_GAME_OVER) {
-      showGameOver();
-      handleGameOver();
+      showGameOver("GAME OVER", gradeSummary());
+      renderStats();
+      if (!app.is_replaying() && !app.is_practice() && app.is_standard()) handleGameOver();
+    } else if (newState === STATE_FINISHED) {
+      renderLines(app.get_lines());
+      showGameOver(app.get_time_remaining_ms() === undefined ? "FINISHED" : "TIME UP", finishSummary());
     } else if (newState === STATE_PAUSED) {
       showPauseOverlay();
     } else if (newState === STATE_PLAYING && oldState !== STATE_PLAYING) {
@@ -164,18 +408,29 @@ function setupCallbacks() {
     }
   });
 
+  // Perfect clear celebration
+  app.set_on_perfect_clear(() => showPerfectClear());
+
   // Score update callback
   app.set_on_score_update((score: number, level: number, lines: number) => {
     scoreEl.textContent = String(score);
     levelEl.textContent = String(level);
-    linesEl.textContent = String(lines);
+    renderLines(lines);
+    renderClearInfo(app.get_combo(), app.is_back_to_back(), app.get_last_clear());
   });
 }
 
+function renderClearInfo(combo: number, backToBack: boolean, lastClear: number) {
+  // Combo counts from the second consecutive clear
+  comboEl.textContent = String(Math.max(combo - 1, 0));
+  clearLabelEl.textContent = get_clear_name(lastClear);
+  b2bLabelEl.textContent = backToBack ? "BACK-TO-BACK" : "";
+}
+
 function setupInputHandlers() {
   const gameKeys = [
     "ArrowLeft", "ArrowRight", "ArrowDown", "ArrowUp", "Space",
-    "KeyX", "KeyZ", "KeyC", "KeyP", "Escape", "Enter", "KeyR",
+    "KeyX", "KeyZ", "KeyA", "KeyC", "KeyP", "Escape", "Enter", "KeyR", "KeyU", "KeyY",
     "ShiftLeft", "ShiftRight", "ControlLeft", "ControlRight"
   ];
 
@@ -192,7 +447,60 @@ function setupInputHandlers() {
 
   window.addEventListener("blur", () => {
     app.on_blur();
+    saveGame();
+  });
+  window.addEventListener("pagehide", saveGame);
+
+  // S saves the replay of a finished game; dropping a replay file plays it back
+  window.addEventListener("keydown", (e) => {
+    const state = app.get_state();
+    if (e.code === "KeyS" && (state === STATE_GAME_OVER || state === STATE_FINISHED)) {
+      saveReplay();
+    }
   });
+  window.addEventListener("dragover", (e) => e.preventDefault());
+  window.addEventListener("drop", async (e) => {
+    e.preventDefault();
+    const file = e.dataTransfer?.files[0];
+    if (file && !app.import_replay(new Uint8Array(await file.arrayBuffer()))) {
+      console.warn("Not a replay file:", file.name);
+    }
+  });
+}
+
+// Offer to resume the game saved when the page was last left
+function resumeSavedGame() {
+  const snapshot = localStorage.getItem(SNAPSHOT_KEY);
+  localStorage.removeItem(SNAPSHOT_KEY);
+  if (snapshot && confirm("Resume your saved game?") && !app.load_snapshot(snapshot)) {
+    console.warn("Saved game is from an older version and can't be resumed");
+  }
+}
+
+// Keep a game in progress for resuming after a reload (it comes back paused)
+function saveGame() {
+  const state = app.get_state();
+  if (state === STATE_PLAYING || state === STATE_PAUSED) {
+    localStorage.setItem(SNAPSHOT_KEY, app.save_snapshot());
+  } else {
+    localStorage.removeItem(SNAPSHOT_KEY);
+  }
+}
+
+function replayBase64(): string {
+  let binary = "";
+  for (const byte of app.export_replay()) binary += String.fromCharCode(byte);
+  return btoa(binary);
+}
+
+function saveReplay() {
+  const bytes = app.export_replay();
+  if (bytes.length === 0) return;
+  const link = document.createElement("a");
+  link.href = URL.createObjectURL(new Blob([bytes], { type: "application/octet-stream" }));
+  link.download = `tetris-${Date.now()}.replay`;
+  link.click();
+  URL.revokeObjectURL(link.href);
 }
 
 function showStartScreen() {
@@ -212,8 +520,10 @@ function showStartScreen() {
           <li><kbd>Space</kbd> Hard drop</li>
           <li><kbd>&uarr;</kbd> <kbd>X</kbd> Rotate CW</li>
           <li><kbd>Z</kbd> Rotate CCW</li>
+          <li><kbd>A</kbd> Rotate 180</li>
           <li><kbd>C</kbd> <kbd>Shift</kbd> Hold</li>
           <li><kbd>P</kbd> <kbd>Esc</kbd> Pause</li>
+          <li><kbd>U</kbd> <kbd>Y</kbd> Undo / redo (practice)</li>
         </ul>
       </div>
       <div id="high-scores"></div>
@@ -240,10 +550,21 @@ function showPauseOverlay() {
   overlay.style.display = "flex";
 }
 
-function showGameOver() {
+function showGameOver(title = "GAME OVER", detail = "") {
+  gameOverOverlay.querySelector(".game-over-text")!.textContent = title;
+  gameOverOverlay.querySelector(".game-over-detail")!.textContent = detail;
   gameOverOverlay.style.display = "flex";
 }
 
+function showPerfectClear() {
+  document.getElementById("perfect-clear")?.remove();
+  const banner = document.createElement("div");
+  banner.id = "perfect-clear";
+  banner.textContent = "PERFECT CLEAR";
+  banner.addEventListener("animationend", () => banner.remove());
+  document.getElementById("game-container")?.appendChild(banner);
+}
+
 function clearOverlay() {
   if (gameOverOverlay) gameOverOverlay.style.display = "none";
   document.getElementById("start-screen")?.style.setProperty("display", "none");
@@ -251,7 +572,7 @@ function clearOverlay() {
 }
 
 async function loadHighScores() {
-  const scores = await getHighScores();
+  const scores = await getHighScores(app.get_ruleset());
   displayHighScores(scores);
   displayInGameScores(scores);
 }
@@ -295,13 +616,14 @@ async function handleGameOver() {
   const score = app.get_score();
   const level = app.get_level();
   const lines = app.get_lines();
-  const scores = await getHighScores();
+  const ruleset = app.get_ruleset();
+  const scores = await getHighScores(ruleset);
   const isHighScore = scores.length < 10 || score > (scores[9]?.score ?? 0);
 
   if (isHighScore && score > 0) {
     const name = prompt(`High Score! Enter your name (Score: ${score.toLocaleString()})`);
     if (name?.trim()) {
-      await submitScore({ name: name.trim().substring(0, 20), score, level, lines });
+      await submitScore({ name: name.trim().substring(0, 20), score, level, lines, ruleset, replay: replayBase64() });
       await loadHighScores();
     }
   }